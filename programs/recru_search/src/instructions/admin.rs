@@ -3,8 +3,11 @@ use crate::state::*;
 
 #[derive(Accounts)]
 pub struct InitializeProtocol<'info> {
+    // init_if_needed so a repeat call reaches the is_initialized check instead of failing
+    // on an opaque account-in-use error. The protocol can only be set up once: there is
+    // no reinitialize instruction, and config changes go through the admin setters.
     #[account(
-        init,
+        init_if_needed,
         payer = protocol_admin,
         space = 8 + AdminAccount::INIT_SPACE,
         seeds = [b"admin"],
@@ -19,6 +22,80 @@ pub struct InitializeProtocol<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+pub struct SetIpfsGateway<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub protocol_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetExtensionApprovalThreshold<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub protocol_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetGracePeriods<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub protocol_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetDistributionRateLimit<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub protocol_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetProtocolPause<'info> {
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub protocol_admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetProtocolStats<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+}
+
 impl<'info> InitializeProtocol<'info> {
     pub fn initialize_protocol(
         &mut self,
@@ -27,6 +104,11 @@ impl<'info> InitializeProtocol<'info> {
         max_study_duration: Option<u32>,
         bumps: &InitializeProtocolBumps,
     ) -> Result<()> {
+        require!(
+            !self.admin_state.is_initialized,
+            RecruSearchError::ProtocolAlreadyInitialized
+        );
+
         // Check that all parameters are valid
         let validated_config = self.validate_protocol_config(
             protocol_fee_basis_points,
@@ -81,7 +163,17 @@ impl<'info> InitializeProtocol<'info> {
         admin_state.max_study_duration = config.max_study_duration as u64;
         admin_state.total_studies = 0;
         admin_state.total_participants = 0;
+        admin_state.unique_participants = 0;
         admin_state.total_rewards_distributed = 0;
+        admin_state.total_active_liability = 0;
+        admin_state.preferred_ipfs_gateway = String::new();
+        admin_state.extension_requires_admin_above_seconds = DEFAULT_EXTENSION_APPROVAL_THRESHOLD;
+        admin_state.withdrawal_grace_period_override = None;
+        admin_state.retention_grace_period_override = None;
+        admin_state.max_distributions_per_window = 0;
+        admin_state.distribution_window_seconds = 0;
+        admin_state.is_initialized = true;
+        admin_state.is_paused = false;
         admin_state.bump = bumps.admin_state;
 
         // Emit protocol initialization event for tracking
@@ -108,6 +200,141 @@ impl<'info> InitializeProtocol<'info> {
     }
 }
 
+impl<'info> SetIpfsGateway<'info> {
+    // Sets the gateway clients should use to fetch submitted data (empty clears it)
+    pub fn set_ipfs_gateway(&mut self, ipfs_gateway: String) -> Result<()> {
+        require!(
+            ipfs_gateway.len() <= MAX_GATEWAY_LENGTH,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        let admin_state = &mut self.admin_state;
+        admin_state.preferred_ipfs_gateway = ipfs_gateway.clone();
+
+        msg!("Preferred IPFS gateway set to: {}", ipfs_gateway);
+
+        emit!(IpfsGatewayUpdated {
+            admin: self.protocol_admin.key(),
+            ipfs_gateway,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetExtensionApprovalThreshold<'info> {
+    // Sets how far a researcher may push study windows out without admin co-signing
+    pub fn set_extension_approval_threshold(&mut self, threshold_seconds: i64) -> Result<()> {
+        require!(threshold_seconds >= 0, RecruSearchError::InvalidParameterValue);
+
+        let admin_state = &mut self.admin_state;
+        admin_state.extension_requires_admin_above_seconds = threshold_seconds;
+
+        msg!("Extensions above {} seconds now require admin approval", threshold_seconds);
+
+        Ok(())
+    }
+}
+
+impl<'info> SetGracePeriods<'info> {
+    // Overrides the protocol grace windows; None falls back to the constant default
+    pub fn set_grace_periods(&mut self, withdrawal: Option<i64>, retention: Option<i64>) -> Result<()> {
+        require!(
+            withdrawal.unwrap_or(0) >= 0 && retention.unwrap_or(0) >= 0,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        let admin_state = &mut self.admin_state;
+        admin_state.withdrawal_grace_period_override = withdrawal;
+        admin_state.retention_grace_period_override = retention;
+
+        msg!(
+            "Grace periods set | Withdrawal: {}s | Retention: {}s",
+            admin_state.withdrawal_grace_period(),
+            admin_state.retention_grace_period()
+        );
+
+        Ok(())
+    }
+}
+
+impl<'info> SetDistributionRateLimit<'info> {
+    // Caps how many rewards one researcher can distribute per window; zero lifts the cap
+    pub fn set_distribution_rate_limit(&mut self, max_per_window: u32, window_seconds: i64) -> Result<()> {
+        require!(
+            max_per_window == 0 || window_seconds > 0,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        let admin_state = &mut self.admin_state;
+        admin_state.max_distributions_per_window = max_per_window;
+        admin_state.distribution_window_seconds = window_seconds;
+
+        msg!("Distribution rate limit set | {} per {}s", max_per_window, window_seconds);
+
+        emit!(DistributionRateLimitUpdated {
+            max_distributions_per_window: max_per_window,
+            distribution_window_seconds: window_seconds,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetProtocolPause<'info> {
+    // Flips the protocol-wide circuit breaker; existing studies resume untouched on unpause
+    pub fn set_protocol_pause(&mut self, paused: bool) -> Result<()> {
+        let admin_state = &mut self.admin_state;
+        require!(
+            admin_state.is_paused != paused,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        admin_state.is_paused = paused;
+
+        msg!("Protocol paused: {}", paused);
+
+        emit!(ProtocolPauseUpdated {
+            admin: self.protocol_admin.key(),
+            is_paused: paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> GetProtocolStats<'info> {
+    // Protocol-wide totals plus the grace windows currently in effect
+    pub fn get_protocol_stats(&self) -> Result<ProtocolStats> {
+        let admin_state = &self.admin_state;
+
+        Ok(ProtocolStats {
+            total_studies: admin_state.total_studies,
+            total_participants: admin_state.total_participants,
+            unique_participants: admin_state.unique_participants,
+            total_rewards_distributed: admin_state.total_rewards_distributed,
+            total_active_liability: admin_state.total_active_liability,
+            withdrawal_grace_period: admin_state.withdrawal_grace_period(),
+            retention_grace_period: admin_state.retention_grace_period(),
+            is_paused: admin_state.is_paused,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProtocolStats {
+    pub total_studies: u64,
+    pub total_participants: u64,
+    pub unique_participants: u64,
+    pub total_rewards_distributed: u64,
+    pub total_active_liability: u64,
+    pub withdrawal_grace_period: i64,
+    pub retention_grace_period: i64,
+    pub is_paused: bool,
+}
+
 // Helper struct to hold the validated RecruSearch config
 #[derive(Debug)]
 struct ProtocolConfig {
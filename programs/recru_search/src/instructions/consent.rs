@@ -1,23 +1,30 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
 use mpl_core::{
     ID as MPL_CORE_ID,
-    instructions::{CreateV1CpiBuilder, BurnV1CpiBuilder},
-    types::{Attribute, Attributes, DataState, PluginAuthorityPair},
+    accounts::BaseAssetV1,
+    fetch_plugin,
+    instructions::{CreateV1CpiBuilder, BurnV1CpiBuilder, UpdatePluginV1CpiBuilder},
+    types::{Attribute, Attributes, DataState, PermanentBurnDelegate, Plugin, PluginAuthority, PluginAuthorityPair, PluginType},
 };
-use crate::state::{StudyAccount, StudyStatus, ConsentAccount, SubmissionAccount, RecruSearchError, CONSENT_NFT_TEMPLATE_IMAGE};
-use crate::instructions::eligibility_criteria::{EligibilityInfo, verify_participant_eligibility};
-use crate::state::events::{ConsentNFTMinted,ConsentRevoked};
+use crate::state::{AdminAccount, StudyAccount, StudyStatus, ConsentAccount, IdentityNullifier, GlobalParticipant, SubmissionAccount, RewardVault, RecruSearchError, study_error, CONSENT_NFT_SYMBOL, CONSENT_NFT_TEMPLATE_IMAGE, CONSENT_STATE_NONE, ELIGIBILITY_METHOD_NONE, ELIGIBILITY_METHOD_CRITERIA, ELIGIBILITY_METHOD_ORACLE, REWARD_CLAIM_DELAY, MAX_MERKLE_PROOF_LENGTH};
+use crate::instructions::rewards::release_revoked_slot;
+use crate::instructions::study::check_count_invariants;
+use crate::instructions::merkle_rewards::verify_merkle_proof;
+use crate::instructions::eligibility_criteria::{EligibilityInfo, verify_participant_eligibility, verified_age_from_attestation};
+use crate::state::events::{ConsentNFTMinted,ConsentRevoked,ConsentRevokedByResearcher,ConsentExpired,ConsentStateChanged};
 
 // Consent NFT - allows participants to enroll in studies
 
 #[derive(Accounts)]
-#[instruction(study_id: u64)]
+#[instruction(study_id: u64, eligibility_proof: Vec<u8>, nullifier: Option<[u8; 32]>)]
 pub struct MintConsentNFT<'info> {
     // Study account to enroll in
     #[account(
         mut,
         seeds = [b"study", study.researcher.as_ref(), study_id.to_le_bytes().as_ref()],
         bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
         constraint = study.status == StudyStatus::Published @ RecruSearchError::StudyNotPublished,
         constraint = study.enrolled_count < study.max_participants @ RecruSearchError::StudyFull
     )]
@@ -37,8 +44,11 @@ pub struct MintConsentNFT<'info> {
     )]
     pub consent: Account<'info, ConsentAccount>,
 
-    /// CHECK: This is the asset account that will be used to mint the NFT
-    #[account(mut)]
+    /// CHECK: This is the asset account that will be used to mint the NFT - must be uninitialized
+    #[account(
+        mut,
+        constraint = asset.data_is_empty() @ RecruSearchError::AssetAlreadyInitialized
+    )]
     pub asset: UncheckedAccount<'info>,
     
     #[account(mut)]
@@ -49,6 +59,45 @@ pub struct MintConsentNFT<'info> {
     /// CHECK: This is the MPL Core program ID which is verified by the address constraint
     #[account(address = MPL_CORE_ID)]
     pub mpl_core_program: UncheckedAccount<'info>,
+
+    /// CHECK: age attestation, required when the study sets an age oracle - owner and contents checked in the handler
+    pub age_attestation: Option<UncheckedAccount<'info>>,
+
+    // Reward vault - source of the reward mint recorded on the consent NFT
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>,
+
+    // Identity nullifier - init_if_needed so reuse surfaces NullifierAlreadyUsed
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + IdentityNullifier::INIT_SPACE,
+        seeds = [b"nullifier", study.key().as_ref(), nullifier.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub identity_nullifier: Option<Account<'info, IdentityNullifier>>,
+
+    // Protocol config - tracks enrollment and unique participant totals
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    // Global participant marker - init_if_needed so only a wallet's first enrollment counts as unique
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + GlobalParticipant::INIT_SPACE,
+        seeds = [b"global_participant", participant.key().as_ref()],
+        bump
+    )]
+    pub global_participant: Account<'info, GlobalParticipant>,
 }
 // Consent revocation - allows participants to withdraw from studies
 #[derive(Accounts)]
@@ -67,15 +116,29 @@ pub struct RevokeConsent<'info> {
     )]
     pub consent: Account<'info, ConsentAccount>,
 
-    // Study account for reference
+    // Study account - tracks revocation count
     #[account(
+        mut,
         seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
-        bump = study.bump
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = consent.study == study.key() @ RecruSearchError::InvalidParameterValue
     )]
     pub study: Account<'info, StudyAccount>,
 
-    /// CHECK: asset account to burn the NFT
-    #[account(mut)]
+    // Reward vault - credited with the revoked participant's reserved reward
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>,
+
+    /// CHECK: asset account to burn, or mark revoked when the study preserves revoked NFTs; verified against the consent record
+    #[account(
+        mut,
+        constraint = consent.nft_mint == Some(asset.key()) @ RecruSearchError::InvalidParameterValue
+    )]
     pub asset: UncheckedAccount<'info>,
 
     // Participant revoking consent
@@ -84,29 +147,236 @@ pub struct RevokeConsent<'info> {
 
     pub system_program: Program<'info, System>,
 
-    
+    /// CHECK: MPL Core program ID which is verified by the address constraint
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+// Researcher revocation - removes a participant for protocol violations
+#[derive(Accounts)]
+pub struct ResearcherRevokeConsent<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::StudyArchived
+    )]
+    pub study: Account<'info, StudyAccount>,
+
     #[account(
+        mut,
         seeds = [
-            b"submission",
+            b"consent",
+            study.key().as_ref(),
+            consent.participant.as_ref()
+        ],
+        bump = consent.bump,
+        constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked,
+        constraint = !consent.is_expired @ RecruSearchError::ConsentExpired
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    // Reward vault - credited with the revoked participant's reserved reward
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>,
+
+    /// CHECK: consent NFT asset, verified against the consent record
+    #[account(
+        mut,
+        constraint = consent.nft_mint == Some(asset.key()) @ RecruSearchError::InvalidParameterValue
+    )]
+    pub asset: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: MPL Core program ID which is verified by the address constraint
+    #[account(address = MPL_CORE_ID)]
+    pub mpl_core_program: UncheckedAccount<'info>,
+}
+
+// Consent expiry opt-in - allows the consent PDA's permanent burn delegate to expire the NFT once the study is over
+#[derive(Accounts)]
+pub struct OptInConsentExpiry<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = consent.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"consent",
             consent.study.as_ref(),
             participant.key().as_ref()
         ],
-        bump
+        bump = consent.bump,
+        constraint = consent.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant,
+        constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked,
+        constraint = !consent.expiry_opt_in @ RecruSearchError::InvalidParameterValue
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    pub participant: Signer<'info>,
+}
+
+// Consent expiry - permissionless cleanup of consent NFTs for finished studies
+#[derive(Accounts)]
+pub struct ExpireConsentNFT<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    // Protocol config - provides the retention grace period
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"consent",
+            study.key().as_ref(),
+            consent.participant.as_ref()
+        ],
+        bump = consent.bump,
+        constraint = consent.expiry_opt_in @ RecruSearchError::ExpiryNotOptedIn,
+        constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked,
+        constraint = !consent.is_expired @ RecruSearchError::ConsentExpired
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    /// CHECK: consent NFT asset, verified against the consent record
+    #[account(
+        mut,
+        constraint = consent.nft_mint == Some(asset.key()) @ RecruSearchError::InvalidParameterValue
     )]
-    pub submission: Option<Account<'info, SubmissionAccount>>,
+    pub asset: UncheckedAccount<'info>,
+
+    // Anyone can trigger expiry once the participant has opted in
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
 
     /// CHECK: MPL Core program ID which is verified by the address constraint
     #[account(address = MPL_CORE_ID)]
     pub mpl_core_program: UncheckedAccount<'info>,
 }
 
+// Consent status - read-only view of a participant's consent
+#[derive(Accounts)]
+pub struct GetConsentStatus<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [
+            b"consent",
+            study.key().as_ref(),
+            consent.participant.as_ref()
+        ],
+        bump = consent.bump
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+}
+
+// Participant journey - one read covering enrollment, submission and reward progress
+#[derive(Accounts)]
+pub struct GetParticipantJourney<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [
+            b"consent",
+            study.key().as_ref(),
+            consent.participant.as_ref()
+        ],
+        bump = consent.bump
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    /// CHECK: latest submission PDA, may not exist yet - read in the handler when present
+    #[account(
+        seeds = [
+            b"submission",
+            study.key().as_ref(),
+            consent.participant.as_ref(),
+            consent.submission_count.saturating_sub(1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub submission: UncheckedAccount<'info>,
+}
+
+// Enrollment check - tells clients whether the consent PDA is already taken, without erroring when it isn't
+#[derive(Accounts)]
+pub struct CheckEnrollment<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    /// CHECK: consent PDA, may not exist - read in the handler when present
+    #[account(
+        seeds = [b"consent", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub consent: UncheckedAccount<'info>,
+
+    /// CHECK: wallet being checked, only used to derive the consent PDA
+    pub participant: UncheckedAccount<'info>,
+}
+
 impl<'info> MintConsentNFT<'info> {
     // Mints consent NFT and enrolls participant in study
-    pub fn mint_consent_nft(&mut self, _study_id: u64, eligibility_proof: Vec<u8>) -> Result<()> {
+    pub fn mint_consent_nft(&mut self, _study_id: u64, eligibility_proof: Vec<u8>, nullifier: Option<[u8; 32]>, invite_proof: Vec<[u8; 32]>, bumps: &MintConsentNFTBumps) -> Result<()> {
         require!(eligibility_proof.len() > 0, RecruSearchError::InvalidEligibilityProof);
         
         let study = &self.study;
         let clock = Clock::get()?;
+
+        // Claim the identity nullifier so the same person can't enroll from another wallet
+        match (nullifier, self.identity_nullifier.as_mut()) {
+            (Some(nullifier), Some(identity_nullifier)) => {
+                require!(
+                    identity_nullifier.participant == Pubkey::default(),
+                    RecruSearchError::NullifierAlreadyUsed
+                );
+                identity_nullifier.study = study.key();
+                identity_nullifier.nullifier = nullifier;
+                identity_nullifier.participant = self.participant.key();
+                identity_nullifier.created_at = clock.unix_timestamp;
+                identity_nullifier.bump = bumps.identity_nullifier.ok_or(RecruSearchError::IdentityRequired)?;
+            }
+            (None, None) => {
+                require!(!study.requires_unique_identity, RecruSearchError::IdentityRequired);
+            }
+            _ => return Err(RecruSearchError::IdentityRequired.into()),
+        }
         
         // Validate enrollment period
         require!(
@@ -114,18 +384,47 @@ impl<'info> MintConsentNFT<'info> {
             clock.unix_timestamp <= study.enrollment_end,
             RecruSearchError::InvalidEnrollmentPeriod
         );
+        // Studies with an age oracle don't trust the self-attested age
+        let verified_age = match study.age_oracle {
+            Some(age_oracle) => Some(verified_age_from_attestation(
+                self.age_attestation.as_ref().map(|a| a.as_ref()),
+                &age_oracle,
+                &self.participant.key(),
+            )?),
+            None => None,
+        };
+
          // Verify eligibility criteria are set
-        if study.has_eligibility_criteria {
-            let participant_info: EligibilityInfo = EligibilityInfo::try_from_slice(&eligibility_proof)
+        let eligibility_method = if study.has_eligibility_criteria {
+            let mut participant_info: EligibilityInfo = EligibilityInfo::try_from_slice(&eligibility_proof)
                 .map_err(|_| RecruSearchError::InvalidEligibilityProof)?;
-            
+            if verified_age.is_some() {
+                participant_info.min_age = verified_age;
+            }
             
             let is_eligible = verify_participant_eligibility(&study.eligibility_criteria, &participant_info)?;
-            require!(is_eligible, RecruSearchError::ParticipantNotEligible);
+            if !is_eligible {
+                return Err(study_error(study.study_id, RecruSearchError::ParticipantNotEligible));
+            }
             
             msg!("Participant eligibility verified successfully");
+            if verified_age.is_some() { ELIGIBILITY_METHOD_ORACLE } else { ELIGIBILITY_METHOD_CRITERIA }
+        } else if verified_age.is_some() {
+            msg!("Participant age verified by oracle");
+            ELIGIBILITY_METHOD_ORACLE
         } else {
             msg!("Study has no eligibility criteria - skipping verification");
+            ELIGIBILITY_METHOD_NONE
+        };
+
+        // Invite-only studies also need the wallet in the invite tree (AND with eligibility)
+        if let Some(invite_root) = study.invite_merkle_root {
+            let invite_leaf = hashv(&[self.participant.key().as_ref()]).to_bytes();
+            require!(
+                invite_proof.len() <= MAX_MERKLE_PROOF_LENGTH
+                    && verify_merkle_proof(&invite_proof, invite_root, invite_leaf),
+                RecruSearchError::NotOnInviteList
+            );
         }
 
         // Initialize consent account
@@ -137,14 +436,43 @@ impl<'info> MintConsentNFT<'info> {
         consent.revocation_timestamp = None;
         consent.eligibility_proof = eligibility_proof;
         consent.nft_mint = Some(self.asset.key());
+        consent.consent_valid_until = study.data_collection_end;
+        consent.expiry_opt_in = false;
+        consent.is_expired = false;
+        consent.eligibility_verified = eligibility_method != ELIGIBILITY_METHOD_NONE;
+        consent.eligibility_method = eligibility_method;
+        consent.revoked_by_researcher = false;
+        consent.revocation_reason = 0;
+        consent.submission_count = 0;
+        consent.reward_recipient = None;
+        consent.bump = bumps.consent;
 
         // Extract study data before borrowing mutably
         let study_id = study.study_id;
         let study_title = study.title.clone();
         let study_researcher = study.researcher;
         let study_has_eligibility = study.has_eligibility_criteria;
+        let reward_amount = study.reward_amount_per_participant;
+        let reward_mint = match (study.reward_vault, &self.reward_vault) {
+            (Some(_), Some(vault)) => vault.reward_token_mint.to_string(),
+            (Some(_), None) => return Err(RecruSearchError::InvalidParameterValue.into()),
+            (None, _) => "None".to_string(),
+        };
         let study = &mut self.study;
         study.enrolled_count = study.enrolled_count.saturating_add(1);
+        check_count_invariants(study)?;
+
+        let admin_state = &mut self.admin_state;
+        admin_state.total_participants = admin_state.total_participants.saturating_add(1);
+
+        let global_participant = &mut self.global_participant;
+        if global_participant.participant == Pubkey::default() {
+            global_participant.participant = self.participant.key();
+            global_participant.first_study = study.key();
+            global_participant.first_enrolled_at = clock.unix_timestamp;
+            global_participant.bump = bumps.global_participant;
+            admin_state.unique_participants = admin_state.unique_participants.saturating_add(1);
+        }
         
         let metadata_uri = CONSENT_NFT_TEMPLATE_IMAGE;
         
@@ -185,6 +513,10 @@ impl<'info> MintConsentNFT<'info> {
                             key: "Platform".to_string(), 
                             value: "RecruSearch".to_string() 
                         },
+                        Attribute { 
+                            key: "Symbol".to_string(), 
+                            value: CONSENT_NFT_SYMBOL.to_string() 
+                        },
                         Attribute { 
                             key: "Researcher".to_string(), 
                             value: study_researcher.to_string()
@@ -192,10 +524,23 @@ impl<'info> MintConsentNFT<'info> {
                         Attribute { 
                             key: "Has Eligibility Criteria".to_string(), 
                             value: study_has_eligibility.to_string()
+                        },
+                        Attribute { 
+                            key: "Reward Amount".to_string(), 
+                            value: reward_amount.to_string()
+                        },
+                        Attribute { 
+                            key: "Reward Mint".to_string(), 
+                            value: reward_mint
                         }
                     ]
                 }), 
                 authority: None
+            },
+            // Lets the consent PDA burn the NFT when the researcher revokes consent
+            PluginAuthorityPair {
+                plugin: Plugin::PermanentBurnDelegate(PermanentBurnDelegate {}),
+                authority: Some(PluginAuthority::Address { address: self.consent.key() }),
             }])
             .invoke()?;
 
@@ -210,6 +555,7 @@ impl<'info> MintConsentNFT<'info> {
             consent_nft_mint: self.asset.key(),
             timestamp: clock.unix_timestamp,
         });
+        emit_consent_state_changed(&self.study, &self.consent, CONSENT_STATE_NONE, clock.unix_timestamp);
 
         Ok(())
     }
@@ -219,26 +565,40 @@ impl<'info> RevokeConsent<'info> {
     // Revokes consent and marks NFT as revoked - prevents data submission
     pub fn revoke_consent(&mut self) -> Result<()> {
         // Prevent revocation after data submission
-        if let Some(_submission) = &self.submission {
+        if self.consent.submission_count > 0 {
             msg!("ERROR: Cannot revoke consent after data submission");
             return Err(RecruSearchError::AlreadySubmitted.into());
         }
 
         let clock = Clock::get()?;
+        let old_state = self.consent.state();
 
         // Mark consent as revoked
         let consent = &mut self.consent;
         consent.is_revoked = true;
         consent.revocation_timestamp = Some(clock.unix_timestamp);
 
-        // Burn the consent NFT
-        BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
-            .asset(&self.asset.to_account_info())
-            .authority(Some(&self.participant.to_account_info()))
-            .invoke()?;
-        
-        msg!("SUCCESS: Consent revoked and NFT burned for participant: {}", self.participant.key());
-        msg!("Burned NFT: {}", self.asset.key());
+        let study = &mut self.study;
+        study.enrolled_count = study.enrolled_count.saturating_sub(1);
+        study.total_revocations = study.total_revocations.saturating_add(1);
+        check_count_invariants(study)?;
+        release_revoked_slot(study, self.reward_vault.as_mut())?;
+
+        if self.study.preserve_revoked_nft {
+            self.mark_nft_revoked(clock.unix_timestamp)?;
+
+            msg!("SUCCESS: Consent revoked and NFT marked revoked for participant: {}", self.participant.key());
+            msg!("Preserved NFT: {}", self.asset.key());
+        } else {
+            // Burn the consent NFT
+            BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+                .asset(&self.asset.to_account_info())
+                .authority(Some(&self.participant.to_account_info()))
+                .invoke()?;
+
+            msg!("SUCCESS: Consent revoked and NFT burned for participant: {}", self.participant.key());
+            msg!("Burned NFT: {}", self.asset.key());
+        }
         
         // Emit consent revoked event
         emit!(ConsentRevoked {
@@ -246,7 +606,290 @@ impl<'info> RevokeConsent<'info> {
             participant: self.participant.key(),
             timestamp: clock.unix_timestamp,
         });
+        emit_consent_state_changed(&self.study, &self.consent, old_state, clock.unix_timestamp);
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl<'info> RevokeConsent<'info> {
+    // Rewrites the asset's attributes with revocation markers, keeping everything set at mint
+    fn mark_nft_revoked(&self, revoked_at: i64) -> Result<()> {
+        let (_, mut attributes, _) = fetch_plugin::<BaseAssetV1, Attributes>(
+            &self.asset.to_account_info(),
+            PluginType::Attributes,
+        )
+        .map_err(|_| RecruSearchError::InvalidParameterValue)?;
+
+        attributes
+            .attribute_list
+            .retain(|attribute| attribute.key != "Revoked" && attribute.key != "Revoked At");
+        attributes.attribute_list.push(Attribute {
+            key: "Revoked".to_string(),
+            value: "true".to_string(),
+        });
+        attributes.attribute_list.push(Attribute {
+            key: "Revoked At".to_string(),
+            value: revoked_at.to_string(),
+        });
+
+        // The participant is the asset's update authority, which owns the Attributes plugin
+        UpdatePluginV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+            .asset(&self.asset.to_account_info())
+            .payer(&self.participant.to_account_info())
+            .authority(Some(&self.participant.to_account_info()))
+            .system_program(&self.system_program.to_account_info())
+            .plugin(Plugin::Attributes(attributes))
+            .invoke()?;
+
+        Ok(())
+    }
+}
+
+impl<'info> ResearcherRevokeConsent<'info> {
+    // Revokes a participant's consent on the researcher's behalf and burns the consent NFT,
+    // unless the study preserves revoked NFTs
+    pub fn researcher_revoke_consent(&mut self, reason_code: u8) -> Result<()> {
+        // A participant who finished their series is owed a reward and can no longer be removed
+        require!(
+            self.consent.submission_count < self.study.submissions_per_participant,
+            RecruSearchError::AlreadySubmitted
+        );
+
+        let clock = Clock::get()?;
+        let old_state = self.consent.state();
+        let study_key = self.study.key();
+        let participant_key = self.consent.participant;
+
+        // The participant holds the NFT's update authority, so a preserved NFT stays as minted
+        // and the consent record alone carries the revocation
+        if !self.study.preserve_revoked_nft {
+            let signer_seeds: &[&[u8]] = &[
+                b"consent",
+                study_key.as_ref(),
+                participant_key.as_ref(),
+                &[self.consent.bump],
+            ];
+
+            // Burn via the consent PDA acting as permanent burn delegate
+            BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+                .asset(&self.asset.to_account_info())
+                .payer(&self.researcher.to_account_info())
+                .authority(Some(&self.consent.to_account_info()))
+                .system_program(Some(&self.system_program.to_account_info()))
+                .invoke_signed(&[signer_seeds])?;
+        }
+
+        let consent = &mut self.consent;
+        consent.is_revoked = true;
+        consent.revocation_timestamp = Some(clock.unix_timestamp);
+        consent.revoked_by_researcher = true;
+        consent.revocation_reason = reason_code;
+
+        let study = &mut self.study;
+        study.enrolled_count = study.enrolled_count.saturating_sub(1);
+        study.total_revocations = study.total_revocations.saturating_add(1);
+        check_count_invariants(study)?;
+        release_revoked_slot(study, self.reward_vault.as_mut())?;
+
+        msg!("Consent revoked by researcher for participant: {}", participant_key);
+        msg!("Reason code: {}", reason_code);
+        if study.preserve_revoked_nft {
+            msg!("Preserved NFT: {}", self.asset.key());
+        } else {
+            msg!("Burned NFT: {}", self.asset.key());
+        }
+
+        emit!(ConsentRevokedByResearcher {
+            study_id: study.study_id,
+            participant: participant_key,
+            researcher: self.researcher.key(),
+            reason_code,
+            timestamp: clock.unix_timestamp,
+        });
+        emit_consent_state_changed(study, &self.consent, old_state, clock.unix_timestamp);
+
+        Ok(())
+    }
+}
+
+impl<'info> OptInConsentExpiry<'info> {
+    // Records the participant's agreement to expiry; the consent PDA already holds a
+    // permanent burn delegate from mint, so no plugin needs adding here
+    pub fn opt_in_consent_expiry(&mut self) -> Result<()> {
+        let consent = &mut self.consent;
+        let old_state = consent.state();
+        consent.expiry_opt_in = true;
+
+        msg!("Consent expiry enabled for participant: {}", self.participant.key());
+        msg!("Consent valid until: {}", consent.consent_valid_until);
+
+        emit_consent_state_changed(&self.study, &self.consent, old_state, Clock::get()?.unix_timestamp);
+
+        Ok(())
+    }
+}
+
+impl<'info> ExpireConsentNFT<'info> {
+    // Burns an opted-in consent NFT once the study is closed and the consent has lapsed
+    pub fn expire_consent_nft(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        let consent = &self.consent;
+        let old_state = consent.state();
+
+        // Keep the NFT through the retention grace window after consent lapses
+        let retention_deadline = consent
+            .consent_valid_until
+            .saturating_add(self.admin_state.retention_grace_period());
+        require!(
+            clock.unix_timestamp > retention_deadline,
+            RecruSearchError::ConsentStillValid
+        );
+
+        let study_key = self.study.key();
+        let participant_key = consent.participant;
+        let signer_seeds: &[&[u8]] = &[
+            b"consent",
+            study_key.as_ref(),
+            participant_key.as_ref(),
+            &[consent.bump],
+        ];
+
+        // Burn via the consent PDA acting as permanent burn delegate
+        BurnV1CpiBuilder::new(&self.mpl_core_program.to_account_info())
+            .asset(&self.asset.to_account_info())
+            .payer(&self.payer.to_account_info())
+            .authority(Some(&self.consent.to_account_info()))
+            .system_program(Some(&self.system_program.to_account_info()))
+            .invoke_signed(&[signer_seeds])?;
+
+        let consent = &mut self.consent;
+        consent.is_expired = true;
+
+        msg!("Consent NFT expired for participant: {}", participant_key);
+        msg!("Burned NFT: {}", self.asset.key());
+
+        emit!(ConsentExpired {
+            study_id: self.study.study_id,
+            participant: participant_key,
+            consent_nft_mint: self.asset.key(),
+            timestamp: clock.unix_timestamp,
+        });
+        emit_consent_state_changed(&self.study, &self.consent, old_state, clock.unix_timestamp);
+
+        Ok(())
+    }
+}
+
+// Emits the uniform audit event; callers capture old_state before mutating the consent
+pub(crate) fn emit_consent_state_changed(study: &StudyAccount, consent: &ConsentAccount, old_state: u8, timestamp: i64) {
+    emit!(ConsentStateChanged {
+        study_id: study.study_id,
+        participant: consent.participant,
+        old_state,
+        new_state: consent.state(),
+        timestamp,
+        notification_config_hash: study.notification_config_hash,
+    });
+}
+
+impl<'info> GetConsentStatus<'info> {
+    // Returns the recorded consent state, including how eligibility was established
+    pub fn get_consent_status(&self) -> Result<ConsentStatus> {
+        let consent = &self.consent;
+
+        Ok(ConsentStatus {
+            study_id: self.study.study_id,
+            participant: consent.participant,
+            timestamp: consent.timestamp,
+            is_revoked: consent.is_revoked,
+            revocation_timestamp: consent.revocation_timestamp,
+            nft_mint: consent.nft_mint,
+            is_expired: consent.is_expired,
+            eligibility_verified: consent.eligibility_verified,
+            eligibility_method: consent.eligibility_method,
+        })
+    }
+}
+
+impl<'info> GetParticipantJourney<'info> {
+    // Summarizes the participant's progress, treating a missing submission as not yet submitted
+    pub fn get_participant_journey(&self) -> Result<ParticipantJourney> {
+        let study = &self.study;
+        let consent = &self.consent;
+
+        let submission = if consent.submission_count > 0 && !self.submission.data_is_empty() {
+            let data = self.submission.try_borrow_data()?;
+            Some(SubmissionAccount::try_deserialize(&mut &data[..])?)
+        } else {
+            None
+        };
+
+        // Rewards open once the final submission in the series has aged past the claim delay
+        let series_complete = consent.submission_count >= study.submissions_per_participant;
+        let claim_available_at = submission
+            .as_ref()
+            .filter(|submission| series_complete && !submission.reward_distributed)
+            .map(|submission| submission.submission_timestamp + REWARD_CLAIM_DELAY);
+
+        Ok(ParticipantJourney {
+            enrolled: !consent.is_revoked && !consent.is_expired,
+            consent_timestamp: consent.timestamp,
+            is_revoked: consent.is_revoked,
+            has_submitted: submission.is_some(),
+            submission_count: consent.submission_count,
+            submission_timestamp: submission.as_ref().map(|submission| submission.submission_timestamp),
+            is_verified: submission.as_ref().is_some_and(|submission| submission.is_verified),
+            reward_distributed: submission.as_ref().is_some_and(|submission| submission.reward_distributed),
+            has_completion_nft: submission.as_ref().is_some_and(|submission| submission.completion_nft_mint.is_some()),
+            claim_available_at,
+        })
+    }
+}
+
+impl<'info> CheckEnrollment<'info> {
+    // A revoked consent still counts as enrolled: its PDA exists, so minting again would fail
+    pub fn check_enrollment(&self) -> Result<EnrollmentCheck> {
+        if self.consent.data_is_empty() {
+            return Ok(EnrollmentCheck { is_enrolled: false, is_revoked: false });
+        }
+
+        let data = self.consent.try_borrow_data()?;
+        let consent = ConsentAccount::try_deserialize(&mut &data[..])?;
+
+        Ok(EnrollmentCheck { is_enrolled: true, is_revoked: consent.is_revoked })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EnrollmentCheck {
+    pub is_enrolled: bool,
+    pub is_revoked: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ParticipantJourney {
+    pub enrolled: bool,
+    pub consent_timestamp: i64,
+    pub is_revoked: bool,
+    pub has_submitted: bool,
+    pub submission_count: u32,
+    pub submission_timestamp: Option<i64>,
+    pub is_verified: bool,
+    pub reward_distributed: bool,
+    pub has_completion_nft: bool,
+    pub claim_available_at: Option<i64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ConsentStatus {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub timestamp: i64,
+    pub is_revoked: bool,
+    pub revocation_timestamp: Option<i64>,
+    pub nft_mint: Option<Pubkey>,
+    pub is_expired: bool,
+    pub eligibility_verified: bool,
+    pub eligibility_method: u8,
+}
@@ -6,10 +6,13 @@ use crate::state::*;
 #[derive(Accounts)]
 #[instruction(study_id: u64)]
 pub struct CreateSurveySchema<'info> {
-   
+    // Surveys can be drafted alongside the study; submissions wait for finalization
     #[account(
+        mut,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
         constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
-        constraint = study.status == StudyStatus::Published @ RecruSearchError::InvalidStatusTransition
+        constraint = !matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState,
+        constraint = study.status == StudyStatus::Draft || study.status == StudyStatus::Published @ RecruSearchError::InvalidStatusTransition
     )]
     pub study: Account<'info, StudyAccount>,
 
@@ -48,6 +51,7 @@ impl<'info> CreateSurveySchema<'info> {
         survey_title: String,
         schema_ipfs_cid: String,
         requires_encryption: bool,
+        estimated_duration_minutes: u16,
         bumps: &CreateSurveySchemaBumps,
     ) -> Result<()> {
         
@@ -56,6 +60,12 @@ impl<'info> CreateSurveySchema<'info> {
             RecruSearchError::TitleTooLong
         );
 
+        // Keep durations within a day so time-based quality checks stay meaningful
+        require!(
+            (MIN_SURVEY_DURATION_MINUTES..=MAX_SURVEY_DURATION_MINUTES).contains(&estimated_duration_minutes),
+            RecruSearchError::InvalidParameterValue
+        );
+
         // Basic IPFS CID validation (length only)
         require!(
             schema_ipfs_cid.len() >= 10 && schema_ipfs_cid.len() <= 100,
@@ -68,14 +78,23 @@ impl<'info> CreateSurveySchema<'info> {
         survey_schema.title = survey_title.clone();
         survey_schema.schema_ipfs_cid = schema_ipfs_cid;
         survey_schema.requires_encryption = requires_encryption;
+        survey_schema.estimated_duration_minutes = estimated_duration_minutes;
+        survey_schema.allowed_cid_prefixes = Vec::new();
         survey_schema.bump = bumps.survey_schema;
 
+        self.study.has_survey_schema = true;
+
        
         let data_stats = &mut self.data_stats;
         data_stats.study = self.study.key();
         data_stats.researcher = self.researcher.key();
         data_stats.total_responses = 0;
         data_stats.complete_responses = 0;
+        data_stats.anonymized_responses = 0;
+        data_stats.total_files = 0;
+        data_stats.total_file_size_bytes = 0;
+        data_stats.validated_responses = 0;
+        data_stats.verified_submissions = 0;
         data_stats.bump = bumps.data_stats;
 
         msg!(
@@ -100,7 +119,11 @@ impl<'info> CreateSurveySchema<'info> {
 pub struct FinalizeSurveySchema<'info> {
     // Study account for validation
     #[account(
-        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+        mut,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Draft | StudyStatus::Published | StudyStatus::Active) @ RecruSearchError::InvalidStudyState,
+        constraint = !study.survey_finalized @ RecruSearchError::InvalidStatusTransition
     )]
     pub study: Account<'info, StudyAccount>,
 
@@ -118,7 +141,8 @@ pub struct FinalizeSurveySchema<'info> {
 impl<'info> FinalizeSurveySchema<'info> {
     // Finalizes survey schema for active data collection
     pub fn finalize_survey_schema(&mut self, study_id: u64) -> Result<()> {
-        
+        self.study.survey_finalized = true;
+
         msg!(
             "Survey schema finalized and activated for study {}: '{}'",
             study_id,
@@ -129,6 +153,145 @@ impl<'info> FinalizeSurveySchema<'info> {
     }
 }
 
+#[derive(Accounts)]
+pub struct SetAllowedCidPrefixes<'info> {
+    // Prefixes are part of the survey definition, so they lock once the survey is finalized
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = !study.survey_finalized @ RecruSearchError::InvalidStatusTransition
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"survey", study.key().as_ref()],
+        bump = survey_schema.bump
+    )]
+    pub survey_schema: Account<'info, SurveySchema>,
+
+    pub researcher: Signer<'info>,
+}
+
+impl<'info> SetAllowedCidPrefixes<'info> {
+    // Replaces the accepted response CID prefixes; an empty list accepts any valid CID
+    pub fn set_allowed_cid_prefixes(&mut self, prefixes: Vec<String>) -> Result<()> {
+        require!(
+            prefixes.len() <= MAX_CID_PREFIXES,
+            RecruSearchError::InvalidParameterValue
+        );
+        require!(
+            prefixes
+                .iter()
+                .all(|prefix| !prefix.is_empty() && prefix.len() <= MAX_CID_PREFIX_LENGTH),
+            RecruSearchError::InvalidParameterValue
+        );
+
+        self.survey_schema.allowed_cid_prefixes = prefixes;
+
+        msg!(
+            "Allowed CID prefixes updated for study {}: {} prefix(es)",
+            self.study.study_id,
+            self.survey_schema.allowed_cid_prefixes.len()
+        );
+
+        Ok(())
+    }
+}
+
+// File upload accounting - sizes are tracked in bytes as u64 so large studies can't wrap
+
+#[derive(Accounts)]
+pub struct RecordFileUpload<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump,
+        constraint = data_stats.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub data_stats: Account<'info, DataCollectionStats>,
+
+    pub researcher: Signer<'info>,
+}
+
+impl<'info> RecordFileUpload<'info> {
+    // Adds an uploaded file to the study's storage totals
+    pub fn record_file_upload(&mut self, file_size_bytes: u64) -> Result<()> {
+        require!(file_size_bytes > 0, RecruSearchError::InvalidParameterValue);
+
+        let stats = &mut self.data_stats;
+        stats.total_file_size_bytes = stats
+            .total_file_size_bytes
+            .checked_add(file_size_bytes)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+        stats.total_files = stats
+            .total_files
+            .checked_add(1)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+
+        msg!(
+            "File upload recorded for study {}: {} bytes ({} files, {} bytes total)",
+            self.study.study_id,
+            file_size_bytes,
+            stats.total_files,
+            stats.total_file_size_bytes
+        );
+
+        Ok(())
+    }
+}
+
+// Anonymization record - the researcher attests the off-chain transform covered every response so far
+
+#[derive(Accounts)]
+pub struct AnonymizeParticipantData<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump,
+        constraint = data_stats.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub data_stats: Account<'info, DataCollectionStats>,
+
+    pub researcher: Signer<'info>,
+}
+
+impl<'info> AnonymizeParticipantData<'info> {
+    // Marks every response collected so far as anonymized
+    pub fn anonymize_participant_data(&mut self) -> Result<()> {
+        let stats = &mut self.data_stats;
+        require!(stats.total_responses > 0, RecruSearchError::InvalidParameterValue);
+
+        stats.anonymized_responses = stats.total_responses;
+
+        msg!(
+            "Anonymization recorded for study {}: {} responses",
+            self.study.study_id,
+            stats.anonymized_responses
+        );
+
+        Ok(())
+    }
+}
+
 #[derive(Accounts)]
 #[instruction(study_id: u64)]
 pub struct ExportSurveyData<'info> {
@@ -142,18 +305,85 @@ pub struct ExportSurveyData<'info> {
 
     #[account(
         seeds = [b"survey", study.key().as_ref()],
-        bump = survey_schema.bump
+        bump = survey_schema.bump,
+        constraint = survey_schema.study == study.key() @ RecruSearchError::InvalidParameterValue
     )]
     pub survey_schema: Account<'info, SurveySchema>,
 
     #[account(
         seeds = [b"data_stats", study.key().as_ref()],
-        bump = data_stats.bump
+        bump = data_stats.bump,
+        constraint = data_stats.study == study.key() @ RecruSearchError::InvalidParameterValue
     )]
     pub data_stats: Account<'info, DataCollectionStats>,
 
+    pub researcher: Signer<'info>,
+}
+
+// Final statistics - snapshots the rollup once the study is closed so later cleanups keep the totals
+
+#[derive(Accounts)]
+pub struct RecordFinalStats<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump,
+        constraint = data_stats.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub data_stats: Account<'info, DataCollectionStats>,
+
+    // init (not init_if_needed) so the snapshot can only be written once
+    #[account(
+        init,
+        payer = researcher,
+        space = 8 + StudyFinalStats::INIT_SPACE,
+        seeds = [b"final_stats", study.key().as_ref()],
+        bump
+    )]
+    pub final_stats: Account<'info, StudyFinalStats>,
+
     #[account(mut)]
     pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetFinalStats<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"final_stats", study.key().as_ref()],
+        bump = final_stats.bump
+    )]
+    pub final_stats: Account<'info, StudyFinalStats>,
+}
+
+// Strength of the transform applied to exported responses
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AnonymizationLevel {
+    None,
+    PseudonymHash,
+    KAnonymity,
+    FullRedaction,
+}
+
+// k_threshold only applies to KAnonymity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AnonymizationConfig {
+    pub level: AnonymizationLevel,
+    pub k_threshold: u8,
 }
 
 impl<'info> ExportSurveyData<'info> {
@@ -161,6 +391,7 @@ impl<'info> ExportSurveyData<'info> {
     pub fn export_survey_data(
         &mut self,
         study_id: u64,
+        anonymization: AnonymizationConfig,
     ) -> Result<ExportManifest> {
         let study = &self.study;
         let stats = &self.data_stats;
@@ -169,11 +400,28 @@ impl<'info> ExportSurveyData<'info> {
             matches!(study.status, StudyStatus::Active | StudyStatus::Closed),
             RecruSearchError::InvalidStatusTransition
         );
+        if anonymization.level == AnonymizationLevel::KAnonymity {
+            require!(
+                anonymization.k_threshold >= MIN_K_ANONYMITY_THRESHOLD,
+                RecruSearchError::InvalidKThreshold
+            );
+        }
+
+        // The transform runs off-chain, so an anonymized export needs anonymize_participant_data first
+        let anonymized = anonymization.level != AnonymizationLevel::None;
+        if anonymized {
+            require!(stats.anonymized_responses > 0, RecruSearchError::AnonymizationFailed);
+        }
+
         let export_manifest = ExportManifest {
             study_id,
             study_title: study.title.clone(),
             total_responses: stats.total_responses,
             complete_responses: stats.complete_responses,
+            anonymized,
+            anonymized_responses: if anonymized { stats.anonymized_responses } else { 0 },
+            anonymization_level: anonymization.level,
+            k_threshold: anonymization.k_threshold,
         };
         msg!(
             "Data export initiated for study {}: '{}' ({} responses)",
@@ -187,10 +435,82 @@ impl<'info> ExportSurveyData<'info> {
 }
 
 
+impl<'info> RecordFinalStats<'info> {
+    // Copies the data collection rollup and closing counters into the immutable snapshot
+    pub fn record_final_stats(&mut self, bumps: &RecordFinalStatsBumps) -> Result<()> {
+        let study = &self.study;
+        let data_stats = &self.data_stats;
+        let clock = Clock::get()?;
+
+        let final_stats = &mut self.final_stats;
+        final_stats.study = study.key();
+        final_stats.total_responses = data_stats.total_responses;
+        final_stats.complete_responses = data_stats.complete_responses;
+        final_stats.validated_responses = data_stats.validated_responses;
+        final_stats.total_files_uploaded = data_stats.total_files;
+        final_stats.total_file_size_bytes = data_stats.total_file_size_bytes;
+        final_stats.enrolled_count = study.enrolled_count;
+        final_stats.completed_count = study.completed_count;
+        final_stats.closed_at = study.closed_at.unwrap_or(clock.unix_timestamp);
+        final_stats.recorded_at = clock.unix_timestamp;
+        final_stats.bump = bumps.final_stats;
+
+        msg!(
+            "Final stats recorded for study {}: {} responses, {} bytes",
+            study.study_id,
+            final_stats.total_responses,
+            final_stats.total_file_size_bytes
+        );
+
+        emit!(FinalStatsRecorded {
+            study_id: study.study_id,
+            total_responses: final_stats.total_responses,
+            total_file_size_bytes: final_stats.total_file_size_bytes,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> GetFinalStats<'info> {
+    pub fn get_final_stats(&self) -> Result<FinalStatsSummary> {
+        let final_stats = &self.final_stats;
+
+        Ok(FinalStatsSummary {
+            total_responses: final_stats.total_responses,
+            complete_responses: final_stats.complete_responses,
+            validated_responses: final_stats.validated_responses,
+            total_files_uploaded: final_stats.total_files_uploaded,
+            total_file_size_bytes: final_stats.total_file_size_bytes,
+            enrolled_count: final_stats.enrolled_count,
+            completed_count: final_stats.completed_count,
+            closed_at: final_stats.closed_at,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct FinalStatsSummary {
+    pub total_responses: u32,
+    pub complete_responses: u32,
+    pub validated_responses: u32,
+    pub total_files_uploaded: u32,
+    pub total_file_size_bytes: u64,
+    pub enrolled_count: u32,
+    pub completed_count: u32,
+    pub closed_at: i64,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct ExportManifest {
     pub study_id: u64,
     pub study_title: String,
     pub total_responses: u32,
     pub complete_responses: u32,
+    // Whether the responses in this export went through the anonymization transform
+    pub anonymized: bool,
+    pub anonymized_responses: u32,
+    pub anonymization_level: AnonymizationLevel,
+    pub k_threshold: u8,
 }
\ No newline at end of file
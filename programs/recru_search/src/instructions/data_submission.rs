@@ -5,22 +5,28 @@ use mpl_core::{
     types::{Attribute, Attributes, DataState, PluginAuthorityPair},
 };
 
+use crate::instructions::study::check_count_invariants;
 use crate::state::*;
 
 // Data submission - allows participants to submit encrypted research data
 
 #[derive(Accounts)]
+#[instruction(encrypted_data_hash: [u8; 32], ipfs_cid: String, submission_index: u32)]
 pub struct SubmitData<'info> {
     // Study account for data submission
     #[account(
+        mut,
         seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump,
-        constraint = study.status == StudyStatus::Published || study.status == StudyStatus::Active @ RecruSearchError::InvalidStudyState
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.status == StudyStatus::Published || study.status == StudyStatus::Active @ RecruSearchError::InvalidStudyState,
+        constraint = !study.has_survey_schema || study.survey_finalized @ RecruSearchError::SurveyNotFinalized
     )]
     pub study: Account<'info, StudyAccount>,
 
-    // Consent account - verifies participant enrollment
+    // Consent account - verifies participant enrollment and tracks submissions made
     #[account(
+        mut,
         seeds = [
             b"consent",
             study.key().as_ref(),
@@ -28,11 +34,13 @@ pub struct SubmitData<'info> {
         ],
         bump = consent.bump,
         constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked,
-        constraint = consent.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant
+        constraint = consent.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant,
+        constraint = submission_index < study.submissions_per_participant @ RecruSearchError::SubmissionLimitReached,
+        constraint = submission_index == consent.submission_count @ RecruSearchError::InvalidParameterValue
     )]
     pub consent: Account<'info, ConsentAccount>,
 
-    // Submission account - stores encrypted data metadata
+    // Submission account - stores encrypted data metadata, one per submission index
     #[account(
         init,
         payer = participant,
@@ -40,15 +48,41 @@ pub struct SubmitData<'info> {
         seeds = [
             b"submission",
             study.key().as_ref(),
-            participant.key().as_ref()
+            participant.key().as_ref(),
+            submission_index.to_le_bytes().as_ref()
         ],
         bump
     )]
     pub submission: Account<'info, SubmissionAccount>,
 
+    // Protocol config - provides the preferred IPFS gateway for events
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
     // Participant submitting data
     #[account(mut)]
     pub participant: Signer<'info>,
+
+    // Data hash record - init_if_needed so a repeated hash surfaces DuplicateSubmissionData
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + DataHashRecord::INIT_SPACE,
+        seeds = [b"datahash", study.key().as_ref(), encrypted_data_hash.as_ref()],
+        bump
+    )]
+    pub data_hash_record: Option<Account<'info, DataHashRecord>>,
+
+    // Survey schema - required when the study has one, supplies the CID prefix allow-list
+    #[account(
+        seeds = [b"survey", study.key().as_ref()],
+        bump = survey_schema.bump
+    )]
+    pub survey_schema: Option<Account<'info, SurveySchema>>,
     
     pub system_program: Program<'info, System>,
 }
@@ -62,27 +96,51 @@ pub struct MintCompletionNFT<'info> {
         mut,
         seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::StudyArchived,
         constraint = study.status == StudyStatus::Active || study.status == StudyStatus::Closed @ RecruSearchError::InvalidStudyState,
-        constraint = study.completed_count < study.max_participants @ RecruSearchError::StudyFull
+        constraint = study.completed_count < study.completion_nft_limit() @ RecruSearchError::CompletionCapReached,
+        constraint = study.reward_type != RewardType::TokenOnly @ RecruSearchError::InvalidRewardType
     )]
     pub study: Account<'info, StudyAccount>,
 
-    // Submission account - verifies data was submitted
+    // Consent account - participant must still hold an active consent
+    #[account(
+        seeds = [
+            b"consent",
+            study.key().as_ref(),
+            participant.key().as_ref()
+        ],
+        bump = consent.bump,
+        constraint = consent.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant,
+        constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    // Submission account - the participant's final submission completes the study
     #[account(
         mut,
         seeds = [
             b"submission",
             study.key().as_ref(),
-            participant.key().as_ref()
+            participant.key().as_ref(),
+            submission.index.to_le_bytes().as_ref()
         ],
         bump = submission.bump,
+        constraint = submission.index + 1 == study.submissions_per_participant @ RecruSearchError::SubmissionSeriesIncomplete,
         constraint = !submission.reward_distributed @ RecruSearchError::InvalidParameterValue,
-        constraint = submission.completion_nft_mint.is_none() @ RecruSearchError::AlreadySubmitted
+        constraint = submission.completion_nft_mint.is_none() @ RecruSearchError::AlreadySubmitted,
+        constraint = !study.requires_verification || submission.is_verified @ RecruSearchError::SubmissionNotVerified,
+        constraint = submission.study == study.key() @ RecruSearchError::InvalidParameterValue,
+        constraint = submission.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant
     )]
     pub submission: Account<'info, SubmissionAccount>,
 
-    /// CHECK: asset account to mint completion NFT
-    #[account(mut)]
+    /// CHECK: asset account to mint completion NFT - must be uninitialized
+    #[account(
+        mut,
+        constraint = asset.data_is_empty() @ RecruSearchError::AssetAlreadyInitialized
+    )]
     pub asset: UncheckedAccount<'info>,
 
 
@@ -96,12 +154,126 @@ pub struct MintCompletionNFT<'info> {
     pub mpl_core_program: UncheckedAccount<'info>,
 }
 
+// Submission status - read-only view of a participant's submission
+
+#[derive(Accounts)]
+pub struct GetSubmissionStatus<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [
+            b"submission",
+            study.key().as_ref(),
+            submission.participant.as_ref(),
+            submission.index.to_le_bytes().as_ref()
+        ],
+        bump = submission.bump
+    )]
+    pub submission: Account<'info, SubmissionAccount>,
+}
+
+// Submission eligibility - read-only check of whether a participant can still submit
+
+#[derive(Accounts)]
+pub struct GetSubmissionEligibility<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    /// CHECK: participant being checked, only used for PDA derivation
+    pub participant: UncheckedAccount<'info>,
+
+    /// CHECK: consent PDA, may not exist if the participant never enrolled
+    #[account(
+        seeds = [b"consent", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub consent: UncheckedAccount<'info>,
+}
+
+// Batch verification - marks several submissions verified in one transaction
+
+#[derive(Accounts)]
+pub struct VerifySubmissionsBatch<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump
+    )]
+    pub data_stats: Account<'info, DataCollectionStats>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Data quality verification - records an auditable validation for one response
+
+#[derive(Accounts)]
+#[instruction(response_id: u32)]
+pub struct VerifyDataQuality<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [
+            b"submission",
+            study.key().as_ref(),
+            submission.participant.as_ref(),
+            submission.index.to_le_bytes().as_ref()
+        ],
+        bump = submission.bump,
+        constraint = submission.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub submission: Account<'info, SubmissionAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        space = 8 + ResponseRecord::INIT_SPACE,
+        seeds = [b"response", submission.key().as_ref(), response_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub response_record: Account<'info, ResponseRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump
+    )]
+    pub data_stats: Account<'info, DataCollectionStats>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 impl<'info> SubmitData<'info> {
     // Submits encrypted research data with IPFS CID
     pub fn submit_data(
         &mut self,
         encrypted_data_hash: [u8; 32],
         ipfs_cid: String,
+        submission_index: u32,
+        encryption_scheme: EncryptionScheme,
         bumps: &SubmitDataBumps,
     ) -> Result<()> {
         let study = &self.study;
@@ -113,30 +285,88 @@ impl<'info> SubmitData<'info> {
             RecruSearchError::InvalidIPFSCID
         );
 
+        match (study.has_survey_schema, self.survey_schema.as_ref()) {
+            (true, Some(survey_schema)) => {
+                require!(
+                    survey_schema.allowed_cid_prefixes.is_empty()
+                        || survey_schema
+                            .allowed_cid_prefixes
+                            .iter()
+                            .any(|prefix| ipfs_cid.starts_with(prefix.as_str())),
+                    RecruSearchError::CidNotAllowed
+                );
+            }
+            (false, None) => {}
+            _ => return Err(RecruSearchError::InvalidParameterValue.into()),
+        }
+
         // Validate data collection period
         require!(
             clock.unix_timestamp <= study.data_collection_end,
             RecruSearchError::InvalidDataCollectionPeriod
         );
 
+        require!(
+            study.allowed_encryption_schemes & encryption_scheme.mask() != 0,
+            RecruSearchError::EncryptionSchemeNotAllowed
+        );
+
+        // Claim the data hash so another submission of identical data is flagged
+        match (study.detect_duplicate_hashes, self.data_hash_record.as_mut()) {
+            (true, Some(data_hash_record)) => {
+                require!(
+                    data_hash_record.participant == Pubkey::default(),
+                    RecruSearchError::DuplicateSubmissionData
+                );
+                data_hash_record.study = study.key();
+                data_hash_record.data_hash = encrypted_data_hash;
+                data_hash_record.participant = self.participant.key();
+                data_hash_record.submission = self.submission.key();
+                data_hash_record.created_at = clock.unix_timestamp;
+                data_hash_record.bump = bumps.data_hash_record.ok_or(RecruSearchError::InvalidParameterValue)?;
+            }
+            (false, None) => {}
+            _ => return Err(RecruSearchError::InvalidParameterValue.into()),
+        }
+
         // Initialize submission account
         let submission = &mut self.submission;
         submission.participant = self.participant.key();
         submission.study = study.key();
+        submission.index = submission_index;
         submission.encrypted_data_hash = encrypted_data_hash;
         submission.ipfs_cid = ipfs_cid.clone();
         submission.submission_timestamp = clock.unix_timestamp;
         submission.reward_distributed = false;
         submission.is_verified = false;
         submission.completion_nft_mint = None;
+        submission.completion_timestamp = 0;
+        submission.bonus_paid = false;
+        submission.reward_amount = 0;
+        submission.encryption_scheme = encryption_scheme;
         submission.bump = bumps.submission;
 
+        let consent = &mut self.consent;
+        consent.submission_count = consent.submission_count.saturating_add(1);
+
+        // A participant counts as submitted once their full series is in
+        let study = &mut self.study;
+        if consent.submission_count == study.submissions_per_participant {
+            study.submitted_count = study.submitted_count.saturating_add(1);
+            check_count_invariants(study)?;
+        }
+        if encryption_scheme != EncryptionScheme::None {
+            study.encrypted_responses = study.encrypted_responses.saturating_add(1);
+        }
+
         // Log submission details
         msg!("Data submitted successfully");
         msg!("Participant: {}", self.participant.key());
         msg!("Study: {}", study.study_id);
         msg!("IPFS CID: {}", ipfs_cid);
+        msg!("Submission {} of {}", submission_index + 1, study.submissions_per_participant);
         msg!("Submission timestamp: {}", clock.unix_timestamp);
+        msg!("Encryption scheme: {:?}", encryption_scheme);
 
         // Emit data submitted event
         emit!(DataSubmitted {
@@ -146,6 +376,22 @@ impl<'info> SubmitData<'info> {
             timestamp: clock.unix_timestamp,
         });
 
+        let ipfs_gateway = self.admin_state.preferred_ipfs_gateway.clone();
+        let gateway_url = if ipfs_gateway.is_empty() {
+            String::new()
+        } else {
+            format!("{}/ipfs/{}", ipfs_gateway.trim_end_matches('/'), ipfs_cid)
+        };
+
+        emit!(DataSubmittedV2 {
+            study_id: study.study_id,
+            participant: self.participant.key(),
+            ipfs_cid,
+            ipfs_gateway,
+            gateway_url,
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
 }
@@ -154,6 +400,7 @@ impl<'info> MintCompletionNFT<'info> {
     // Mint completion NFT as reward for study participation
     pub fn mint_completion_nft(&mut self) -> Result<()> {
         let study = &self.study;
+        let clock = Clock::get()?;
         let submission_timestamp = self.submission.submission_timestamp;
         let metadata_uri = COMPLETION_NFT_TEMPLATE_IMAGE.to_string();
         
@@ -184,7 +431,7 @@ impl<'info> MintCompletionNFT<'info> {
                         },
                         Attribute { 
                             key: "Completion Date".to_string(), 
-                            value: Clock::get()?.unix_timestamp.to_string()
+                            value: clock.unix_timestamp.to_string()
                         },
                         Attribute { 
                             key: "Type".to_string(), 
@@ -194,6 +441,10 @@ impl<'info> MintCompletionNFT<'info> {
                             key: "Platform".to_string(), 
                             value: "RecruSearch".to_string() 
                         },
+                        Attribute { 
+                            key: "Symbol".to_string(), 
+                            value: COMPLETION_NFT_SYMBOL.to_string() 
+                        },
                         Attribute { 
                             key: "Researcher".to_string(), 
                             value: study.researcher.to_string()
@@ -215,12 +466,13 @@ impl<'info> MintCompletionNFT<'info> {
         // Update submission with NFT mint
         let submission = &mut self.submission;
         submission.completion_nft_mint = Some(self.asset.key());
+        submission.completion_timestamp = clock.unix_timestamp;
 
         let study_id = study.study_id;
         let study = &mut self.study;
         study.completed_count = study.completed_count.saturating_add(1);
+        check_count_invariants(study)?;
 
-       
         msg!("SUCCESS: Completion NFT minted for participant: {}", self.participant.key());
         msg!("Completion NFT mint: {}", self.asset.key());
         msg!("Study ID: {}", study_id);
@@ -231,9 +483,178 @@ impl<'info> MintCompletionNFT<'info> {
             study_id: study_id,
             participant: self.participant.key(),
             completion_nft_mint: self.asset.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+impl<'info> VerifySubmissionsBatch<'info> {
+    // Verifies each submission passed in remaining accounts, skipping already-verified ones
+    pub fn verify_submissions_batch(&mut self, submissions: &'info [AccountInfo<'info>]) -> Result<u32> {
+        require!(
+            !submissions.is_empty() && submissions.len() <= MAX_BATCH_SIZE,
+            RecruSearchError::BatchTooLarge
+        );
+
+        let study_key = self.study.key();
+        let mut verified_count: u32 = 0;
+
+        for info in submissions.iter() {
+            require!(info.is_writable, RecruSearchError::InvalidParameterValue);
+
+            let mut submission: Account<'info, SubmissionAccount> = Account::try_from(info)?;
+            require!(
+                submission.study == study_key,
+                RecruSearchError::InvalidParameterValue
+            );
+
+            if submission.is_verified {
+                msg!("Submission already verified, skipping: {}", info.key());
+                continue;
+            }
+
+            submission.is_verified = true;
+            submission.exit(&crate::ID)?;
+            verified_count += 1;
+        }
+
+        // validated_responses belongs to verify_data_quality, so the same response isn't counted twice
+        let data_stats = &mut self.data_stats;
+        data_stats.verified_submissions = data_stats
+            .verified_submissions
+            .checked_add(verified_count)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+
+        msg!("Verified {} submissions for study {}", verified_count, self.study.study_id);
+
+        emit!(SubmissionsVerified {
+            study_id: self.study.study_id,
+            verified_count,
             timestamp: Clock::get()?.unix_timestamp,
         });
 
+        Ok(verified_count)
+    }
+}
+
+impl<'info> GetSubmissionStatus<'info> {
+    // Returns the current state of a participant's submission
+    pub fn get_submission_status(&self) -> Result<SubmissionStatus> {
+        let submission = &self.submission;
+
+        Ok(SubmissionStatus {
+            study_id: self.study.study_id,
+            participant: submission.participant,
+            submission_index: submission.index,
+            submission_timestamp: submission.submission_timestamp,
+            is_verified: submission.is_verified,
+            reward_distributed: submission.reward_distributed,
+            completion_nft_mint: submission.completion_nft_mint,
+            completion_timestamp: submission.completion_timestamp,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SubmissionStatus {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub submission_index: u32,
+    pub submission_timestamp: i64,
+    pub is_verified: bool,
+    pub reward_distributed: bool,
+    pub completion_nft_mint: Option<Pubkey>,
+    pub completion_timestamp: i64,
+}
+
+impl<'info> GetSubmissionEligibility<'info> {
+    // Mirrors the submit_data checks so participants see the deadline before hitting it
+    pub fn get_submission_eligibility(&self) -> Result<SubmissionEligibility> {
+        let study = &self.study;
+        let now = Clock::get()?.unix_timestamp;
+        let deadline = study.data_collection_end;
+
+        let reason = if !matches!(study.status, StudyStatus::Published | StudyStatus::Active) {
+            Some(SUBMISSION_BLOCKED_STUDY_STATE)
+        } else if now > deadline {
+            Some(SUBMISSION_BLOCKED_DEADLINE_PASSED)
+        } else if self.consent.data_is_empty() {
+            Some(SUBMISSION_BLOCKED_NOT_ENROLLED)
+        } else {
+            let data = self.consent.try_borrow_data()?;
+            let consent = ConsentAccount::try_deserialize(&mut &data[..])?;
+            if consent.submission_count >= study.submissions_per_participant {
+                Some(SUBMISSION_BLOCKED_ALREADY_SUBMITTED)
+            } else if consent.is_revoked || consent.is_expired {
+                Some(SUBMISSION_BLOCKED_CONSENT_INACTIVE)
+            } else {
+                None
+            }
+        };
+
+        Ok(SubmissionEligibility {
+            can_submit: reason.is_none(),
+            deadline,
+            seconds_remaining: deadline.saturating_sub(now).max(0),
+            reason,
+        })
+    }
+}
+
+impl<'info> VerifyDataQuality<'info> {
+    // Records a response validation; re-validating updates the record without recounting it
+    pub fn verify_data_quality(
+        &mut self,
+        response_id: u32,
+        quality_score: u8,
+        bumps: &VerifyDataQualityBumps,
+    ) -> Result<()> {
+        require!(
+            quality_score <= MAX_QUALITY_SCORE,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        let clock = Clock::get()?;
+        let record = &mut self.response_record;
+        let is_new_record = record.validated_at == 0;
+
+        record.submission = self.submission.key();
+        record.response_id = response_id;
+        record.quality_score = quality_score;
+        record.validated_at = clock.unix_timestamp;
+        record.validator = self.researcher.key();
+        record.bump = bumps.response_record;
+
+        if is_new_record {
+            let data_stats = &mut self.data_stats;
+            data_stats.validated_responses = data_stats
+                .validated_responses
+                .checked_add(1)
+                .ok_or(RecruSearchError::ArithmeticError)?;
+        } else {
+            msg!("Response {} re-validated, counter unchanged", response_id);
+        }
+
+        msg!("Response {} validated with quality score {}", response_id, quality_score);
+
+        emit!(ResponseValidated {
+            study_id: self.study.study_id,
+            submission: self.submission.key(),
+            response_id,
+            quality_score,
+            validator: self.researcher.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct SubmissionEligibility {
+    pub can_submit: bool,
+    pub deadline: i64,
+    pub seconds_remaining: i64,
+    pub reason: Option<u8>,
+}
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+// Distribution ledger - on-chain payout history for tax and reporting exports
+
+#[derive(Accounts)]
+pub struct InitializeDistributionLedger<'info> {
+    #[account(
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        init,
+        payer = researcher,
+        space = 8 + std::mem::size_of::<DistributionLedger>(),
+        seeds = [b"ledger", study.key().as_ref()],
+        bump
+    )]
+    pub distribution_ledger: AccountLoader<'info, DistributionLedger>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Ledger page - read-only slice of payouts in distribution order
+
+#[derive(Accounts)]
+pub struct GetDistributionLedgerPage<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"ledger", study.key().as_ref()],
+        bump
+    )]
+    pub distribution_ledger: AccountLoader<'info, DistributionLedger>,
+}
+
+// Appends a payout when the study keeps a ledger. A full ledger must not block payouts;
+// RewardDistributed events remain the complete record.
+pub(crate) fn record_payout(
+    ledger: Option<&AccountLoader<DistributionLedger>>,
+    participant: Pubkey,
+    amount: u64,
+    timestamp: i64,
+) -> Result<()> {
+    if let Some(ledger) = ledger {
+        if !ledger.load_mut()?.append(participant, amount, timestamp) {
+            msg!("Distribution ledger full, payout not recorded in ledger");
+        }
+    }
+
+    Ok(())
+}
+
+impl<'info> InitializeDistributionLedger<'info> {
+    pub fn initialize_distribution_ledger(&mut self, bumps: &InitializeDistributionLedgerBumps) -> Result<()> {
+        let mut ledger = self.distribution_ledger.load_init()?;
+        ledger.study = self.study.key();
+        ledger.entry_count = 0;
+        ledger.bump = bumps.distribution_ledger;
+
+        msg!("Distribution ledger initialized for study {}", self.study.study_id);
+
+        Ok(())
+    }
+}
+
+impl<'info> GetDistributionLedgerPage<'info> {
+    // Returns up to `limit` entries starting at `offset`; an offset past the end yields an empty page
+    pub fn get_distribution_ledger_page(&self, offset: u32, limit: u32) -> Result<LedgerPage> {
+        require!(
+            limit > 0 && limit <= MAX_LEDGER_PAGE_SIZE,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        let ledger = self.distribution_ledger.load()?;
+        let start = (offset as usize).min(ledger.entry_count as usize);
+        let end = start.saturating_add(limit as usize).min(ledger.entry_count as usize);
+
+        let entries = ledger.entries[start..end]
+            .iter()
+            .map(|entry| LedgerRecord {
+                participant: entry.participant,
+                amount: entry.amount,
+                timestamp: entry.timestamp,
+            })
+            .collect();
+
+        Ok(LedgerPage {
+            total_entries: ledger.entry_count,
+            is_full: ledger.entry_count as usize >= MAX_LEDGER_ENTRIES,
+            entries,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LedgerRecord {
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LedgerPage {
+    pub total_entries: u32,
+    pub is_full: bool,
+    pub entries: Vec<LedgerRecord>,
+}
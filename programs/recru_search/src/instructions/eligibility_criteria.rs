@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::{StudyAccount, RecruSearchError, MAX_ELIGIBILITY_CRITERIA_SIZE, MIN_AGE_LIMIT, MAX_AGE_LIMIT};
+use crate::state::{StudyAccount, StudyStatus, CriteriaVersion, RecruSearchError, EligibilityCriteriaVersioned, EligibilityCriteriaLocked, MAX_ELIGIBILITY_CRITERIA_SIZE, MIN_AGE_LIMIT, MAX_AGE_LIMIT};
 
+// Single borsh layout for both stored criteria and participant proofs; set time, the enrollment
+// verifier and the summary read all decode with it, so stored bytes always round-trip
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct EligibilityInfo {
     pub min_age: Option<u8>,        
@@ -9,6 +11,13 @@ pub struct EligibilityInfo {
     pub location: Option<String>,    
 }
 
+// Leading bytes of an attestation account written by a study's age oracle program
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AgeAttestation {
+    pub participant: Pubkey,
+    pub verified_age: u8,
+}
+
 // Study account constraint for eligibility criteria
 #[derive(Accounts)]
 #[instruction(study_id: u64)]
@@ -18,12 +27,75 @@ pub struct SetEligibilityCriteria<'info> {
         mut,
         seeds = [b"study", study.researcher.as_ref(), study_id.to_le_bytes().as_ref()],
         bump = study.bump,
-        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = !matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState,
+        constraint = !study.criteria_locked @ RecruSearchError::EligibilityLocked
     )]
     pub study: Account<'info, StudyAccount>,
 
+    // Immutable snapshot of this criteria version
+    #[account(
+        init,
+        payer = researcher,
+        space = 8 + CriteriaVersion::INIT_SPACE,
+        seeds = [
+            b"criteria_version",
+            study.key().as_ref(),
+            (study.criteria_version + 1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub criteria_version: Account<'info, CriteriaVersion>,
+
     #[account(mut)]
     pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Permanently freezes a study's eligibility criteria (e.g. after IRB approval)
+#[derive(Accounts)]
+pub struct LockEligibilityCriteria<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = !study.criteria_locked @ RecruSearchError::EligibilityLocked,
+        constraint = study.has_eligibility_criteria @ RecruSearchError::InvalidParameterValue
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Read a historical criteria version
+#[derive(Accounts)]
+#[instruction(version: u32)]
+pub struct GetCriteriaVersion<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"criteria_version", study.key().as_ref(), version.to_le_bytes().as_ref()],
+        bump = criteria_version.bump
+    )]
+    pub criteria_version: Account<'info, CriteriaVersion>,
+}
+
+// Eligibility summary - how a study gates enrollment, without decoding criteria client-side
+#[derive(Accounts)]
+pub struct GetEligibilitySummary<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
 }
 
 impl<'info> SetEligibilityCriteria<'info> {
@@ -31,6 +103,7 @@ impl<'info> SetEligibilityCriteria<'info> {
         &mut self,
         study_id: u64,
         criteria_bytes: Vec<u8>,
+        bumps: &SetEligibilityCriteriaBumps,
     ) -> Result<()> {
         let study = &mut self.study;
         require!(
@@ -52,16 +125,133 @@ impl<'info> SetEligibilityCriteria<'info> {
         }
 
         // Store validated criteria
-        study.eligibility_criteria = criteria_bytes;
+        study.eligibility_criteria = criteria_bytes.clone();
         study.has_eligibility_criteria = true;
+        study.criteria_version += 1;
+
+        // Append the new version to the audit log
+        let clock = Clock::get()?;
+        let criteria_version = &mut self.criteria_version;
+        criteria_version.study = study.key();
+        criteria_version.version = study.criteria_version;
+        criteria_version.criteria = criteria_bytes;
+        criteria_version.created_at = clock.unix_timestamp;
+        criteria_version.bump = bumps.criteria_version;
 
         msg!("Eligibility criteria set for study: {}", study_id);
-        msg!("Criteria stored successfully");
+        msg!("Criteria stored successfully as version {}", study.criteria_version);
+
+        emit!(EligibilityCriteriaVersioned {
+            study_id,
+            version: study.criteria_version,
+            timestamp: clock.unix_timestamp,
+        });
 
         Ok(())
     }
 }
 
+impl<'info> LockEligibilityCriteria<'info> {
+    pub fn lock_eligibility_criteria(&mut self) -> Result<()> {
+        let study = &mut self.study;
+        let clock = Clock::get()?;
+
+        study.criteria_locked = true;
+
+        msg!("Eligibility criteria locked for study {} at version {}", study.study_id, study.criteria_version);
+
+        emit!(EligibilityCriteriaLocked {
+            study_id: study.study_id,
+            version: study.criteria_version,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> GetCriteriaVersion<'info> {
+    // Returns the criteria exactly as recorded for the requested version
+    pub fn get_criteria_version(&self, _version: u32) -> Result<CriteriaSnapshot> {
+        let criteria_version = &self.criteria_version;
+
+        Ok(CriteriaSnapshot {
+            study_id: self.study.study_id,
+            version: criteria_version.version,
+            criteria: criteria_version.criteria.clone(),
+            created_at: criteria_version.created_at,
+        })
+    }
+}
+
+impl<'info> GetEligibilitySummary<'info> {
+    // Flags for each gate the study applies; age limits come from the current criteria
+    pub fn get_eligibility_summary(&self) -> Result<EligibilitySummary> {
+        let study = &self.study;
+
+        let criteria = if study.has_eligibility_criteria {
+            Some(
+                EligibilityInfo::try_from_slice(&study.eligibility_criteria)
+                    .map_err(|_| RecruSearchError::InvalidDataFormat)?,
+            )
+        } else {
+            None
+        };
+        let min_age = criteria.as_ref().and_then(|criteria| criteria.min_age);
+        let max_age = criteria.as_ref().and_then(|criteria| criteria.max_age);
+
+        Ok(EligibilitySummary {
+            has_criteria: study.has_eligibility_criteria,
+            criteria_locked: study.criteria_locked,
+            requires_unique_identity: study.requires_unique_identity,
+            uses_age_oracle: study.age_oracle.is_some(),
+            min_age,
+            max_age,
+            has_age_limits: min_age.is_some() || max_age.is_some(),
+            has_gender_filter: criteria.as_ref().is_some_and(|criteria| criteria.gender.is_some()),
+            has_location_filter: criteria.as_ref().is_some_and(|criteria| criteria.location.is_some()),
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EligibilitySummary {
+    pub has_criteria: bool,
+    pub criteria_locked: bool,
+    pub requires_unique_identity: bool,
+    pub uses_age_oracle: bool,
+    pub min_age: Option<u8>,
+    pub max_age: Option<u8>,
+    pub has_age_limits: bool,
+    pub has_gender_filter: bool,
+    pub has_location_filter: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct CriteriaSnapshot {
+    pub study_id: u64,
+    pub version: u32,
+    pub criteria: Vec<u8>,
+    pub created_at: i64,
+}
+
+// Reads the participant's age from an attestation owned by the study's age oracle
+pub fn verified_age_from_attestation(
+    attestation: Option<&AccountInfo>,
+    age_oracle: &Pubkey,
+    participant: &Pubkey,
+) -> Result<u8> {
+    let attestation = attestation.ok_or(RecruSearchError::AgeNotVerified)?;
+    require!(attestation.owner == age_oracle, RecruSearchError::AgeNotVerified);
+
+    let data = attestation.try_borrow_data()?;
+    let record = AgeAttestation::deserialize(&mut &data[..])
+        .map_err(|_| RecruSearchError::AgeNotVerified)?;
+    require!(record.participant == *participant, RecruSearchError::AgeNotVerified);
+
+    Ok(record.verified_age)
+}
+
 // Verify participant eligibility against study criteria
 pub fn verify_participant_eligibility(
     study_eligibility_criteria: &[u8],
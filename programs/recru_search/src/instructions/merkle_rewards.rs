@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+use crate::instructions::distribution_ledger::record_payout;
+use crate::instructions::rewards::{unreserved_balance, vault_signer_seeds};
+use crate::state::*;
+
+// Merkle root - researcher publishes (participant, amount) pairs for retroactive rewards
+
+#[derive(Accounts)]
+pub struct SetRewardMerkleRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::StudyArchived
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Merkle claim - participant proves their leaf and is paid from the vault
+
+#[derive(Accounts)]
+pub struct ClaimMerkleReward<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Protocol config - tracks protocol-wide reward totals
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    // Claim record - init_if_needed so a second claim surfaces RewardAlreadyClaimed
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + MerkleClaim::INIT_SPACE,
+        seeds = [b"merkle_claim", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub merkle_claim: Account<'info, MerkleClaim>,
+
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    // Distribution ledger - payouts are appended when the study has initialized one
+    #[account(
+        mut,
+        seeds = [b"ledger", study.key().as_ref()],
+        bump
+    )]
+    pub distribution_ledger: Option<AccountLoader<'info, DistributionLedger>>,
+
+    #[account(
+        init_if_needed,
+        payer = participant,
+        associated_token::mint = reward_mint,
+        associated_token::authority = participant,
+        associated_token::token_program = token_program,
+    )]
+    pub participant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub participant: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Invite list - researcher publishes the root of the invited wallets, None opens enrollment
+
+#[derive(Accounts)]
+pub struct SetInviteMerkleRoot<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Draft | StudyStatus::Published) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+impl<'info> SetInviteMerkleRoot<'info> {
+    // Invites can be reissued while enrollment is still possible
+    pub fn set_invite_merkle_root(&mut self, invite_merkle_root: Option<[u8; 32]>) -> Result<()> {
+        let study = &mut self.study;
+        study.invite_merkle_root = invite_merkle_root;
+
+        msg!("Invite merkle root for study {}: {:?}", study.study_id, invite_merkle_root);
+
+        emit!(InviteMerkleRootSet {
+            study_id: study.study_id,
+            invite_merkle_root,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetRewardMerkleRoot<'info> {
+    // Stores the root of the (participant, amount) recipient tree
+    pub fn set_reward_merkle_root(&mut self, merkle_root: [u8; 32]) -> Result<()> {
+        let study = &mut self.study;
+        study.reward_merkle_root = Some(merkle_root);
+
+        msg!("Reward merkle root set for study {}", study.study_id);
+
+        emit!(RewardMerkleRootSet {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            merkle_root,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ClaimMerkleReward<'info> {
+    // Verifies the participant's leaf against the stored root and pays the proven amount
+    pub fn claim_merkle_reward(
+        &mut self,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+        bumps: &ClaimMerkleRewardBumps,
+    ) -> Result<()> {
+        let study = &self.study;
+        let root = study.reward_merkle_root.ok_or(RecruSearchError::MerkleRootNotSet)?;
+
+        require!(!study.is_reward_paused, RecruSearchError::RewardsPaused);
+        require!(self.merkle_claim.claimed_at == 0, RecruSearchError::RewardAlreadyClaimed);
+        require!(proof.len() <= MAX_MERKLE_PROOF_LENGTH, RecruSearchError::InvalidMerkleProof);
+        require!(
+            verify_merkle_proof(&proof, root, merkle_leaf(&self.participant.key(), amount)),
+            RecruSearchError::InvalidMerkleProof
+        );
+        // Merkle payouts were never reserved, so they may only draw on what enrolled participants aren't owed
+        require!(
+            unreserved_balance(study, &self.reward_vault, self.vault_token_account.amount)? >= amount,
+            RecruSearchError::InsufficientFunds
+        );
+
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), self.reward_vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.participant_token_account.to_account_info(),
+            authority: self.reward_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        let clock = Clock::get()?;
+        let merkle_claim = &mut self.merkle_claim;
+        merkle_claim.study = self.study.key();
+        merkle_claim.participant = self.participant.key();
+        merkle_claim.amount = amount;
+        merkle_claim.claimed_at = clock.unix_timestamp;
+        merkle_claim.bump = bumps.merkle_claim;
+
+        let vault = &mut self.reward_vault;
+        vault.total_distributed = vault.total_distributed.saturating_add(amount);
+
+        let study = &mut self.study;
+        study.total_rewards_distributed = study.total_rewards_distributed.saturating_add(amount);
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, clock.unix_timestamp);
+
+        let admin_state = &mut self.admin_state;
+        admin_state.total_rewards_distributed = admin_state.total_rewards_distributed.saturating_add(amount);
+        record_payout(self.distribution_ledger.as_ref(), self.participant.key(), amount, clock.unix_timestamp)?;
+
+        msg!("Merkle reward of {} tokens claimed by {}", amount, self.participant.key());
+
+        emit!(RewardDistributed {
+            study_id: study.study_id,
+            participant: self.participant.key(),
+            amount,
+            vault_remaining_balance: self.vault_token_account.amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+// Leaf = sha256(participant || amount as little-endian u64)
+fn merkle_leaf(participant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[participant.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+// Sorted-pair hashing, so proofs don't need left/right flags
+pub(crate) fn verify_merkle_proof(proof: &[[u8; 32]], root: [u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == root
+}
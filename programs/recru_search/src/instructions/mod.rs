@@ -1,17 +1,23 @@
 pub mod study;
 pub mod rewards;
+pub mod merkle_rewards;
+pub mod distribution_ledger;
 pub mod consent;
 pub mod data_submission;
 pub mod admin;
 
 pub mod data_management;
 pub mod eligibility_criteria;
+pub mod rating;
 
 pub use study::*;
 pub use rewards::*;
+pub use merkle_rewards::*;
+pub use distribution_ledger::*;
 pub use consent::*;
 pub use data_submission::*;
 pub use admin::*;
 
 pub use data_management::*;
 pub use eligibility_criteria::*;
+pub use rating::*;
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+// Study rating - completed participants rate the study to build researcher reputation
+
+#[derive(Accounts)]
+pub struct SubmitStudyRating<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    // Submission account - proves the participant completed the study
+    #[account(
+        seeds = [b"submission", study.key().as_ref(), participant.key().as_ref(), submission.index.to_le_bytes().as_ref()],
+        bump = submission.bump,
+        constraint = submission.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant
+    )]
+    pub submission: Account<'info, SubmissionAccount>,
+
+    // Rating account - init_if_needed so a second rating surfaces AlreadyRated
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + StudyRating::INIT_SPACE,
+        seeds = [b"rating", study.key().as_ref(), participant.key().as_ref()],
+        bump
+    )]
+    pub rating: Account<'info, StudyRating>,
+
+    // Researcher profile - aggregates ratings across the researcher's studies
+    #[account(
+        init_if_needed,
+        payer = participant,
+        space = 8 + ResearcherProfile::INIT_SPACE,
+        seeds = [b"researcher_profile", study.researcher.as_ref()],
+        bump
+    )]
+    pub researcher_profile: Account<'info, ResearcherProfile>,
+
+    #[account(mut)]
+    pub participant: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SubmitStudyRating<'info> {
+    // Records a 1-5 rating and adds it to the study's and researcher's running totals
+    pub fn submit_study_rating(
+        &mut self,
+        score: u8,
+        comment_hash: Option<[u8; 32]>,
+        bumps: &SubmitStudyRatingBumps,
+    ) -> Result<()> {
+        require!(
+            (MIN_STUDY_RATING..=MAX_STUDY_RATING).contains(&score),
+            RecruSearchError::InvalidRatingScore
+        );
+        require!(self.rating.rated_at == 0, RecruSearchError::AlreadyRated);
+
+        // Completion is the completion NFT, or the payout for token-only studies
+        let submission = &self.submission;
+        require!(
+            submission.completion_nft_mint.is_some() || submission.reward_distributed,
+            RecruSearchError::StudyNotCompleted
+        );
+
+        let clock = Clock::get()?;
+        let rating = &mut self.rating;
+        rating.study = self.study.key();
+        rating.participant = self.participant.key();
+        rating.score = score;
+        rating.comment_hash = comment_hash;
+        rating.rated_at = clock.unix_timestamp;
+        rating.bump = bumps.rating;
+
+        let study = &mut self.study;
+        study.rating_sum = study.rating_sum.saturating_add(score as u64);
+        study.rating_count = study.rating_count.saturating_add(1);
+
+        let researcher_profile = &mut self.researcher_profile;
+        if researcher_profile.researcher == Pubkey::default() {
+            researcher_profile.researcher = study.researcher;
+            researcher_profile.bump = bumps.researcher_profile;
+        }
+        researcher_profile.rating_sum = researcher_profile.rating_sum.saturating_add(score as u64);
+        researcher_profile.rating_count = researcher_profile.rating_count.saturating_add(1);
+
+        msg!("Study {} rated {} by {}", study.study_id, score, self.participant.key());
+
+        emit!(StudyRated {
+            study_id: study.study_id,
+            participant: self.participant.key(),
+            score,
+            rating_count: study.rating_count,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
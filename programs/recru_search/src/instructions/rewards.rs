@@ -1,9 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token_interface::{Mint, TokenAccount, TokenInterface},
-    token::{transfer_checked, TransferChecked},
+    associated_token::{create_idempotent, get_associated_token_address_with_program_id, AssociatedToken, Create},
+    token_interface::{close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked},
 };
+use crate::instructions::distribution_ledger::record_payout;
+use crate::instructions::consent::emit_consent_state_changed;
+use crate::instructions::study::check_count_invariants;
 use crate::state::*;
 
 // transfers tokens to participants for study completion
@@ -15,6 +17,7 @@ pub struct DistributeReward<'info> {
         mut,
         seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
         constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
     )]
     pub study: Account<'info, StudyAccount>,
@@ -28,6 +31,25 @@ pub struct DistributeReward<'info> {
     )]
     pub reward_vault: Account<'info, RewardVault>,
 
+    // Protocol config - tracks protocol-wide reward totals
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    // Researcher profile - counts distributions against the protocol rate limit
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        space = 8 + ResearcherProfile::INIT_SPACE,
+        seeds = [b"researcher_profile", researcher.key().as_ref()],
+        bump
+    )]
+    pub researcher_profile: Account<'info, ResearcherProfile>,
+
     // Vault token account - source of reward tokens
     #[account(
         mut,
@@ -51,37 +73,56 @@ pub struct DistributeReward<'info> {
     )]
     pub consent: Account<'info, ConsentAccount>,
 
-    // Submission account - verifies data submission and prevents double claims
+    // Submission account - the final submission in the series, prevents double claims
     #[account(
         mut,
         seeds = [
             b"submission",
             study.key().as_ref(),
-            participant.key().as_ref()
+            participant.key().as_ref(),
+            submission.index.to_le_bytes().as_ref()
         ],
         bump = submission.bump,
+        constraint = submission.index + 1 == study.submissions_per_participant @ RecruSearchError::SubmissionSeriesIncomplete,
         constraint = !submission.reward_distributed @ RecruSearchError::RewardAlreadyClaimed,
+        constraint = submission.study == study.key() @ RecruSearchError::InvalidParameterValue,
         constraint = submission.participant == participant.key() @ RecruSearchError::UnauthorizedParticipant
     )]
     pub submission: Account<'info, SubmissionAccount>,
 
+    // Distribution ledger - payouts are appended when the study has initialized one
+    #[account(
+        mut,
+        seeds = [b"ledger", study.key().as_ref()],
+        bump
+    )]
+    pub distribution_ledger: Option<AccountLoader<'info, DistributionLedger>>,
+
     // Reward token mint
     #[account(mut)]
     pub reward_mint: InterfaceAccount<'info, Mint>,
 
-    // Participant token account - destination for rewards
+    /// CHECK: recipient ATA for the reward mint, created in the handler if missing so the rent payer can follow study.ata_rent_payer
     #[account(
-        init_if_needed,
-        payer = participant,
-        associated_token::mint = reward_mint,
-        associated_token::authority = participant
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &reward_recipient.key(),
+            &reward_mint.key(),
+            &token_program.key()
+        ) @ RecruSearchError::InvalidParameterValue
     )]
-    pub participant_token_account: InterfaceAccount<'info, TokenAccount>,
+    pub participant_token_account: UncheckedAccount<'info>,
 
-    /// CHECK: This is the participant account that will receive the reward
+    /// CHECK: This is the participant account whose submission is being rewarded
     #[account(mut)]
     pub participant: UncheckedAccount<'info>,
 
+    /// CHECK: payout wallet - the participant unless they set a reward recipient on their consent
+    #[account(
+        address = consent.reward_recipient.unwrap_or(participant.key()) @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_recipient: UncheckedAccount<'info>,
+
     // Researcher authorizing reward distribution
     #[account(mut)]
     pub researcher: Signer<'info>,
@@ -91,33 +132,54 @@ pub struct DistributeReward<'info> {
     pub system_program: Program<'info, System>,
 }
 
-// Reward vault creation - sets up token vault for study rewards
+// Reward pause - halts payouts without affecting enrollment or submissions
 
 #[derive(Accounts)]
-#[instruction(study_id: u64)]
-pub struct CreateRewardVault<'info> {
-    // Study account for vault association
+pub struct SetRewardPause<'info> {
     #[account(
-        seeds = [b"study", researcher.key().as_ref(), study_id.to_le_bytes().as_ref()],
-        bump = study.bump
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
     )]
     pub study: Account<'info, StudyAccount>,
 
-    // Reward vault account - manages study rewards
+    pub researcher: Signer<'info>,
+}
+
+// Revoked slot reclaim - returns the reward reserved for a revoked participant to the researcher
+
+#[derive(Accounts)]
+pub struct ReclaimRevokedSlot<'info> {
     #[account(
-        init,
-        payer = researcher,
-        space = 8 + RewardVault::INIT_SPACE,
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
         seeds = [b"vault", study.key().as_ref()],
-        bump
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
     )]
     pub reward_vault: Account<'info, RewardVault>,
 
-    // Vault token account - holds reward tokens
+    // Protocol config - tracks protocol-wide active liability
     #[account(
-        init,
-        payer = researcher,
-        token::mint = reward_token_mint,
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
         token::authority = reward_vault,
         token::token_program = token_program,
         seeds = [b"vault_token", reward_vault.key().as_ref()],
@@ -125,155 +187,1449 @@ pub struct CreateRewardVault<'info> {
     )]
     pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    // Reward token mint
-    pub reward_token_mint: InterfaceAccount<'info, Mint>,
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
-    // Researcher token account - source of initial deposit
     #[account(
-        init_if_needed,
-        payer = researcher,
-        associated_token::mint = reward_token_mint,
+        mut,
+        associated_token::mint = reward_mint,
         associated_token::authority = researcher,
         associated_token::token_program = token_program,
     )]
     pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
 
-    // Researcher creating the vault
-    #[account(mut)]
     pub researcher: Signer<'info>,
 
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
-    pub system_program: Program<'info, System>,
 }
 
-impl<'info> CreateRewardVault<'info> {
-    // Creates reward vault and deposits initial tokens
-    pub fn create_reward_vault(
-        &mut self,
-        study_id: u64,
-        initial_deposit: u64,
-        bumps: &CreateRewardVaultBumps,
-    ) -> Result<()> {
-        let study = &self.study;
-        let vault = &mut self.reward_vault;
-
-        // Validate sufficient initial deposit
-        let total_reward_needed = study.reward_amount_per_participant * study.max_participants as u64;
-        require!(
-            initial_deposit >= total_reward_needed,
-            RecruSearchError::InsufficientFunds
-        );
+// Excess withdrawal - returns over-funding once enrollment has ended, before the study closes
 
-        require!(
-            self.researcher_token_account.amount >= initial_deposit,
-            RecruSearchError::InsufficientFunds
-        );
+#[derive(Accounts)]
+pub struct WithdrawExcessFunding<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Published | StudyStatus::Active) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
 
-        // Initialize vault account
-        vault.study = study.key();
-        vault.reward_token_mint = self.reward_token_mint.key();
-        vault.total_deposited = initial_deposit;
-        vault.total_distributed = 0;
-        vault.bump = bumps.reward_vault;
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
 
-        // Transfer tokens from researcher to vault
-        let cpi_accounts = TransferChecked {
-            from: self.researcher_token_account.to_account_info(),
-            mint: self.reward_token_mint.to_account_info(),
-            to: self.vault_token_account.to_account_info(),
-            authority: self.researcher.to_account_info(),
-        };
+    // Protocol config - tracks protocol-wide active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
 
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
-        transfer_checked(
-            cpi_ctx,
-            initial_deposit,
-            self.reward_token_mint.decimals,
-        )?;
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
-        // Log vault creation details
-        msg!("Reward vault created successfully");
-        msg!("Study ID: {}", study_id);
-        msg!("Initial deposit: {} tokens", initial_deposit);
-        msg!("Vault: {}", vault.key());
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = researcher,
+        associated_token::token_program = token_program,
+    )]
+    pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
 
-        // Emit reward vault created event
-        emit!(RewardVaultCreated {
-            study_id,
-            researcher: self.researcher.key(),
-            reward_mint: self.reward_token_mint.key(),
-            initial_deposit,
-        });
+    pub researcher: Signer<'info>,
 
-        Ok(())
-    }
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
-// Helper function for vault signer seeds
-fn vault_signer_seeds(study_key: &Pubkey, vault_bump: u8) -> ([u8; 5], Vec<u8>, [u8; 1]) {
-    (b"vault".clone(), study_key.to_bytes().to_vec(), [vault_bump])
-}
+// Vault top-up - adds tokens to an existing vault, still subject to the funding cap
 
-impl<'info> DistributeReward<'info> {
-    // Distributes reward tokens to participant after verification
-    pub fn distribute_reward(&mut self, _bumps: &DistributeRewardBumps) -> Result<()> {
-        let study = &self.study;
-        let submission = &mut self.submission;
-        let vault = &mut self.reward_vault;
+#[derive(Accounts)]
+pub struct TopUpRewardVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
 
-        let clock = Clock::get()?;
-        
-        // Validate study is in active state
-        require!(
-            study.status == StudyStatus::Active,
-            RecruSearchError::InvalidStudyState
-        );
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
 
-        // Enforce minimum time before claiming (24 hours)
-        let min_time_before_claim = 24 * 60 * 60; // 24 hours
-        require!(
-            clock.unix_timestamp >= submission.submission_timestamp + min_time_before_claim,
-            RecruSearchError::InvalidDataCollectionPeriod
-        );
+    // Protocol config - tracks protocol-wide active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
 
-        // Validate sufficient vault balance
-        let vault_token_balance = self.vault_token_account.amount;
-        require!(
-            vault_token_balance >= study.reward_amount_per_participant,
-            RecruSearchError::InsufficientFunds
-        );
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
 
-        let reward_amount = study.reward_amount_per_participant;
-        
-        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), vault.bump);
-        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
-        let signer_seeds = &[signer_seeds];
-        
-        // Transfer tokens from vault to participant
-        let cpi_accounts = TransferChecked {
-            from: self.vault_token_account.to_account_info(),
-            mint: self.reward_mint.to_account_info(),
-            to: self.participant_token_account.to_account_info(),
-            authority: vault.to_account_info(),
-        };
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
 
-        let cpi_program = self.token_program.to_account_info();
-        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = researcher,
+        associated_token::token_program = token_program,
+    )]
+    pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
 
-        transfer_checked(
-            cpi_ctx,
-            reward_amount,
-            self.reward_mint.decimals,
-        )?;
+    pub researcher: Signer<'info>,
 
-        vault.total_distributed = vault.total_distributed.saturating_add(reward_amount);
-        submission.reward_distributed = true;
+    pub token_program: Interface<'info, TokenInterface>,
+}
 
-        let study = &mut self.study;
-        study.total_rewards_distributed = study.total_rewards_distributed.saturating_add(reward_amount);
+// Leftover withdrawal - returns what a closed study's vault still holds and closes the vault
+
+#[derive(Accounts)]
+pub struct WithdrawRemainingRewards<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        close = researcher,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    // Protocol config - tracks protocol-wide active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = researcher,
+        associated_token::token_program = token_program,
+    )]
+    pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Expired study sweep - anyone may archive a study once its claim deadline passes, for a small fee
+
+#[derive(Accounts)]
+pub struct FinalizeExpiredStudy<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        // A study nobody transitioned is still Published, so it can expire from there too
+        constraint = matches!(study.status, StudyStatus::Published | StudyStatus::Active | StudyStatus::Closed) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    // Protocol config - supplies the grace period and tracks active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: study researcher, only receives the swept balance
+    #[account(address = study.researcher @ RecruSearchError::UnauthorizedResearcher)]
+    pub researcher: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = reward_mint,
+        associated_token::authority = researcher,
+        associated_token::token_program = token_program,
+    )]
+    pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = keeper,
+        associated_token::mint = reward_mint,
+        associated_token::authority = keeper,
+        associated_token::token_program = token_program,
+    )]
+    pub keeper_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Stray token recovery - returns tokens of any other mint sent to the vault by mistake
+
+#[derive(Accounts)]
+pub struct RecoverStrayTokens<'info> {
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = admin_state.protocol_admin == protocol_admin.key() @ RecruSearchError::UnauthorizedAccess
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    // The reward mint is never recoverable, whichever account holds it
+    #[account(
+        constraint = stray_mint.key() != reward_vault.reward_token_mint @ RecruSearchError::RewardMintNotRecoverable
+    )]
+    pub stray_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = stray_mint,
+        associated_token::authority = reward_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub stray_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = stray_mint,
+        token::token_program = token_program,
+    )]
+    pub recipient_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub protocol_admin: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Distribution dry run - read-only payout cost for all outstanding submissions
+
+#[derive(Accounts)]
+pub struct SimulateDistribution<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    #[account(
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+// Reward recipient - participant redirects their payout wallet before being rewarded
+
+#[derive(Accounts)]
+pub struct UpdateRewardRecipient<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"consent", study.key().as_ref(), participant.key().as_ref()],
+        bump = consent.bump,
+        constraint = !consent.is_revoked @ RecruSearchError::ConsentRevoked
+    )]
+    pub consent: Account<'info, ConsentAccount>,
+
+    /// CHECK: final submission PDA, may not exist yet - inspected in the handler so a paid reward can't be redirected
+    #[account(
+        seeds = [
+            b"submission",
+            study.key().as_ref(),
+            participant.key().as_ref(),
+            study.submissions_per_participant.saturating_sub(1).to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub submission: UncheckedAccount<'info>,
+
+    pub participant: Signer<'info>,
+}
+
+// Completion bonus - pays the bonus pool pro-rata once the completion-rate target is met
+
+#[derive(Accounts)]
+pub struct ReleaseCompletionBonus<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status == StudyStatus::Closed @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump,
+        constraint = reward_vault.study == study.key() @ RecruSearchError::InvalidParameterValue
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    // Protocol config - tracks protocol-wide active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(address = reward_vault.reward_token_mint @ RecruSearchError::InvalidParameterValue)]
+    pub reward_mint: InterfaceAccount<'info, Mint>,
+
+    // Distribution ledger - payouts are appended when the study has initialized one
+    #[account(
+        mut,
+        seeds = [b"ledger", study.key().as_ref()],
+        bump
+    )]
+    pub distribution_ledger: Option<AccountLoader<'info, DistributionLedger>>,
+
+    pub researcher: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Reward vault creation - sets up token vault for study rewards
+
+#[derive(Accounts)]
+#[instruction(study_id: u64)]
+pub struct CreateRewardVault<'info> {
+    // Study account for vault association
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.reward_type != RewardType::NftOnly @ RecruSearchError::InvalidRewardType,
+        // A withdrawn vault clears reward_vault, so a closed study must not open a fresh one
+        constraint = !matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState,
+        constraint = study.reward_vault.is_none() @ RecruSearchError::VaultAlreadyExists
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    // Reward vault account - manages study rewards
+    // init_if_needed so a repeat call reaches the VaultAlreadyExists checks instead of failing in the system program
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        space = 8 + RewardVault::INIT_SPACE,
+        seeds = [b"vault", study.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: Account<'info, RewardVault>,
+
+    // Protocol config - tracks protocol-wide active liability
+    #[account(
+        mut,
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    // Vault token account - holds reward tokens
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        token::mint = reward_token_mint,
+        token::authority = reward_vault,
+        token::token_program = token_program,
+        seeds = [b"vault_token", reward_vault.key().as_ref()],
+        bump
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Reward token mint
+    pub reward_token_mint: InterfaceAccount<'info, Mint>,
+
+    // Researcher token account - source of initial deposit
+    // Owner and mint are checked explicitly as well, since init_if_needed skips creation for an existing account
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        associated_token::mint = reward_token_mint,
+        associated_token::authority = researcher,
+        associated_token::token_program = token_program,
+        constraint = researcher_token_account.owner == researcher.key() @ RecruSearchError::TokenAccountMismatch,
+        constraint = researcher_token_account.mint == reward_token_mint.key() @ RecruSearchError::TokenAccountMismatch,
+    )]
+    pub researcher_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Researcher creating the vault
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateRewardVault<'info> {
+    // Creates reward vault and deposits initial tokens
+    pub fn create_reward_vault(
+        &mut self,
+        study_id: u64,
+        initial_deposit: u64,
+        allow_overfund: bool,
+        bumps: &CreateRewardVaultBumps,
+    ) -> Result<()> {
+        let study = &mut self.study;
+        let vault = &mut self.reward_vault;
+        require!(vault.study == Pubkey::default(), RecruSearchError::VaultAlreadyExists);
+
+        // Validate sufficient initial deposit
+        let total_reward_needed = max_reward_liability(study)?;
+        require!(
+            initial_deposit >= total_reward_needed,
+            RecruSearchError::InsufficientFunds
+        );
+        validate_vault_funding(initial_deposit, total_reward_needed, allow_overfund)?;
+
+        require!(
+            self.researcher_token_account.amount >= initial_deposit,
+            RecruSearchError::InsufficientFunds
+        );
+
+        // Initialize vault account
+        vault.study = study.key();
+        vault.reward_token_mint = self.reward_token_mint.key();
+        vault.reward_mint_decimals = self.reward_token_mint.decimals;
+        vault.total_deposited = initial_deposit;
+        vault.total_distributed = 0;
+        vault.reclaimable_slots = 0;
+        vault.reserved_liability = total_reward_needed;
+        vault.bump = bumps.reward_vault;
+        study.reward_vault = Some(vault.key());
+
+        let admin_state = &mut self.admin_state;
+        admin_state.total_active_liability = admin_state
+            .total_active_liability
+            .checked_add(total_reward_needed)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+
+        // Transfer tokens from researcher to vault
+        let cpi_accounts = TransferChecked {
+            from: self.researcher_token_account.to_account_info(),
+            mint: self.reward_token_mint.to_account_info(),
+            to: self.vault_token_account.to_account_info(),
+            authority: self.researcher.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+
+        transfer_checked(
+            cpi_ctx,
+            initial_deposit,
+            self.reward_token_mint.decimals,
+        )?;
+
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, Clock::get()?.unix_timestamp);
+
+        // Log vault creation details
+        msg!("Reward vault created successfully");
+        msg!("Study ID: {}", study_id);
+        msg!("Initial deposit: {} tokens", initial_deposit);
+        msg!("Vault: {}", vault.key());
+
+        // Emit reward vault created event
+        emit!(RewardVaultCreated {
+            study_id,
+            researcher: self.researcher.key(),
+            reward_mint: self.reward_token_mint.key(),
+            initial_deposit,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetRewardPause<'info> {
+    // Pauses or resumes reward distribution for the study
+    pub fn set_reward_pause(&mut self, paused: bool) -> Result<()> {
+        let study = &mut self.study;
+        require!(
+            study.is_reward_paused != paused,
+            RecruSearchError::InvalidParameterValue
+        );
+
+        study.is_reward_paused = paused;
+
+        msg!("Study {} rewards paused: {}", study.study_id, paused);
+
+        emit!(RewardPauseUpdated {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            is_paused: paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ReclaimRevokedSlot<'info> {
+    // Withdraws one revoked participant's reward and shrinks the study by that slot
+    pub fn reclaim_revoked_slot(&mut self) -> Result<()> {
+        let study = &mut self.study;
+        let vault = &mut self.reward_vault;
+
+        require!(vault.reclaimable_slots > 0, RecruSearchError::NoReclaimableSlots);
+        // Slots refilled by later enrollments are owed to those participants
+        require!(
+            study.max_participants > study.enrolled_count,
+            RecruSearchError::NoReclaimableSlots
+        );
+
+        // Release exactly what the slot reserved, which covers the variable reward ceiling.
+        // An earlier excess withdrawal may already have returned it.
+        let amount = slot_liability(study);
+        require!(
+            unreserved_balance(study, vault, self.vault_token_account.amount)? >= amount,
+            RecruSearchError::InsufficientFunds
+        );
+
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.researcher_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, Clock::get()?.unix_timestamp);
+
+        vault.reclaimable_slots -= 1;
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+        study.max_participants -= 1;
+        release_liability(vault, &mut self.admin_state, amount);
+
+        msg!("Reclaimed {} tokens for a revoked slot in study {}", amount, study.study_id);
+        msg!("Remaining reclaimable slots: {}", vault.reclaimable_slots);
+
+        emit!(RevokedSlotReclaimed {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            amount,
+            remaining_reclaimable_slots: vault.reclaimable_slots,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> WithdrawExcessFunding<'info> {
+    // Returns up to the deposit not owed to enrolled participants. Paid participants stay counted,
+    // so the reserve only ever errs toward the participants.
+    pub fn withdraw_excess_funding(&mut self, amount: u64) -> Result<()> {
+        let study = &mut self.study;
+        let vault = &mut self.reward_vault;
+        let clock = Clock::get()?;
+
+        require!(clock.unix_timestamp > study.enrollment_end, RecruSearchError::EnrollmentStillOpen);
+        require!(amount > 0, RecruSearchError::InvalidParameterValue);
+
+        let obligation = reward_liability(study, study.enrolled_count)?;
+        let excess = unreserved_balance(study, vault, self.vault_token_account.amount)?;
+        require!(amount <= excess, RecruSearchError::WithdrawalExceedsExcess);
+
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.researcher_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, clock.unix_timestamp);
+
+        // Capacity is left as is; anyone enrolling in an extended window is funded by a top-up.
+        // Only the reservation for unfilled slots is released, never what enrolled participants are owed.
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+        let unfilled_reservation = vault.reserved_liability.saturating_sub(obligation);
+        release_liability(vault, &mut self.admin_state, amount.min(unfilled_reservation));
+
+        msg!("Withdrew {} excess tokens from study {}", amount, study.study_id);
+        msg!("Remaining obligation: {}", obligation);
+
+        emit!(ExcessFundingWithdrawn {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            amount,
+            remaining_obligation: obligation,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> TopUpRewardVault<'info> {
+    // Deposits more reward tokens; the unspent balance may not exceed the funding cap unless overridden
+    pub fn top_up_reward_vault(&mut self, amount: u64, allow_overfund: bool) -> Result<()> {
+        let study = &mut self.study;
+        let vault = &mut self.reward_vault;
+        let clock = Clock::get()?;
+
+        require!(amount > 0, RecruSearchError::InvalidParameterValue);
+        require!(
+            self.researcher_token_account.amount >= amount,
+            RecruSearchError::InsufficientFunds
+        );
+
+        let total_deposited = vault
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+        let max_liability = max_reward_liability(study)?;
+        validate_vault_funding(
+            total_deposited.saturating_sub(vault.total_distributed),
+            max_liability,
+            allow_overfund,
+        )?;
+
+        let cpi_accounts = TransferChecked {
+            from: self.researcher_token_account.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.vault_token_account.to_account_info(),
+            authority: self.researcher.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(self.token_program.to_account_info(), cpi_accounts);
+        transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, clock.unix_timestamp);
+        vault.total_deposited = total_deposited;
+
+        // Re-reserve capacity an earlier excess withdrawal released, up to what the study can still owe
+        let unreserved_capacity = max_liability
+            .saturating_sub(vault.total_distributed)
+            .saturating_sub(vault.reserved_liability);
+        let newly_reserved = amount.min(unreserved_capacity);
+        vault.reserved_liability += newly_reserved;
+        let admin_state = &mut self.admin_state;
+        admin_state.total_active_liability = admin_state
+            .total_active_liability
+            .checked_add(newly_reserved)
+            .ok_or(RecruSearchError::ArithmeticError)?;
+
+        msg!("Topped up study {} vault with {} tokens", study.study_id, amount);
+        msg!("Total deposited: {}", total_deposited);
+
+        emit!(RewardVaultToppedUp {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> WithdrawRemainingRewards<'info> {
+    // Only once every submitted participant and any earned completion bonus has been paid, or the
+    // claim window has closed, so nobody eligible is left stranded
+    pub fn withdraw_remaining_rewards(&mut self) -> Result<()> {
+        let study = &mut self.study;
+        let vault = &mut self.reward_vault;
+        let clock = Clock::get()?;
+
+        if clock.unix_timestamp <= reward_claim_deadline(study, &self.admin_state) {
+            require!(
+                study.rewarded_count >= study.submitted_count,
+                RecruSearchError::UnpaidSubmissionsRemain
+            );
+            require!(
+                study.completion_bonus_pool == 0
+                    || study.completion_bonus_released
+                    || !completion_target_met(study),
+                RecruSearchError::UnpaidSubmissionsRemain
+            );
+        }
+
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let amount = self.vault_token_account.amount;
+        if amount > 0 {
+            let cpi_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.reward_mint.to_account_info(),
+                to: self.researcher_token_account.to_account_info(),
+                authority: vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+            transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+        }
+
+        // The emptied token account goes too, returning its rent to the researcher
+        let cpi_accounts = CloseAccount {
+            account: self.vault_token_account.to_account_info(),
+            destination: self.researcher.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        close_account(cpi_ctx)?;
+
+        // The vault closes with this instruction, so whatever it still reserved is released in full
+        vault.total_deposited = vault.total_deposited.saturating_sub(amount);
+        vault.reclaimable_slots = 0;
+        let reserved = vault.reserved_liability;
+        release_liability(vault, &mut self.admin_state, reserved);
+
+        study.reward_vault = None;
+        study.cache_vault_balance(0, clock.unix_timestamp);
+
+        msg!("Withdrew {} remaining tokens from study {}", amount, study.study_id);
+        msg!("Reward vault {} closed", vault.key());
+
+        emit!(RewardsWithdrawn {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            amount,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> FinalizeExpiredStudy<'info> {
+    // Sweeps unclaimed rewards back to the researcher, pays the keeper and archives the study
+    pub fn finalize_expired_study(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        let deadline = reward_claim_deadline(&self.study, &self.admin_state);
+        require!(clock.unix_timestamp > deadline, RecruSearchError::ClaimDeadlineNotReached);
+
+        let remaining = self.vault_token_account.amount;
+        let keeper_fee = (remaining as u128 * KEEPER_FEE_BPS as u128 / 10_000) as u64;
+        let returned_to_researcher = remaining - keeper_fee;
+
+        let study_key = self.study.key();
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study_key, self.reward_vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        for (to, amount) in [
+            (self.keeper_token_account.to_account_info(), keeper_fee),
+            (self.researcher_token_account.to_account_info(), returned_to_researcher),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            let cpi_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.reward_mint.to_account_info(),
+                to,
+                authority: self.reward_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+            transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+        }
+
+        self.vault_token_account.reload()?;
+
+        // Whatever was still reserved is forfeited once the claim window closes
+        let vault = &mut self.reward_vault;
+        vault.total_deposited = vault.total_deposited.saturating_sub(remaining);
+        vault.reclaimable_slots = 0;
+        let reserved = vault.reserved_liability;
+        release_liability(vault, &mut self.admin_state, reserved);
+
+        let study = &mut self.study;
+        study.cache_vault_balance(self.vault_token_account.amount, clock.unix_timestamp);
+        // Published or Active studies are archived here without passing through close_study
+        study.closed_at.get_or_insert(clock.unix_timestamp);
+        study.status = StudyStatus::Archived;
+        study.archived_at = Some(clock.unix_timestamp);
+
+        msg!(
+            "Expired study {} archived by keeper {} | fee {} | returned {}",
+            study.study_id,
+            self.keeper.key(),
+            keeper_fee,
+            returned_to_researcher
+        );
+
+        emit!(StudyArchived {
+            study_id: study.study_id,
+            researcher: study.researcher,
+            timestamp: clock.unix_timestamp,
+        });
+        emit!(ExpiredStudyFinalized {
+            study_id: study.study_id,
+            keeper: self.keeper.key(),
+            keeper_fee,
+            returned_to_researcher,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> RecoverStrayTokens<'info> {
+    // Moves the full stray balance to the recipient using the vault PDA as signer
+    pub fn recover_stray_tokens(&mut self) -> Result<()> {
+        let amount = self.stray_token_account.amount;
+        require!(amount > 0, RecruSearchError::InsufficientFunds);
+
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&self.study.key(), self.reward_vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let cpi_accounts = TransferChecked {
+            from: self.stray_token_account.to_account_info(),
+            mint: self.stray_mint.to_account_info(),
+            to: self.recipient_token_account.to_account_info(),
+            authority: self.reward_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+        transfer_checked(cpi_ctx, amount, self.stray_mint.decimals)?;
+
+        msg!("Recovered {} stray tokens of mint {} from study {} vault", amount, self.stray_mint.key(), self.study.study_id);
+
+        emit!(StrayTokensRecovered {
+            study_id: self.study.study_id,
+            mint: self.stray_mint.key(),
+            recipient: self.recipient_token_account.key(),
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SimulateDistribution<'info> {
+    // Prices paying every submitted-but-unpaid participant against the vault balance
+    pub fn simulate_distribution(&self) -> Result<DistributionSimulation> {
+        let study = &self.study;
+
+        // Pool studies owe whatever is left of the pool to the remaining completers
+        let (eligible_count, total_required) = if study.pool_split_mode {
+            (
+                study.submitted_count.saturating_sub(study.pool_paid_count),
+                study.reward_pool.saturating_sub(study.pool_distributed),
+            )
+        } else {
+            let reward_amount = study.reward_amount_per_participant;
+            let eligible_count = study.submitted_count.saturating_sub(study.rewarded_count);
+
+            let total_required = reward_amount
+                .checked_mul(eligible_count as u64)
+                .ok_or(RecruSearchError::ArithmeticError)?;
+            (eligible_count, total_required)
+        };
+        let vault_balance = self.vault_token_account.amount;
+
+        Ok(DistributionSimulation {
+            eligible_count,
+            total_required,
+            vault_sufficient: vault_balance >= total_required,
+            shortfall: total_required.saturating_sub(vault_balance),
+            reward_mint_decimals: self.reward_vault.reward_mint_decimals,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct DistributionSimulation {
+    pub eligible_count: u32,
+    pub total_required: u64,
+    pub vault_sufficient: bool,
+    pub shortfall: u64,
+    pub reward_mint_decimals: u8,
+}
+
+impl<'info> UpdateRewardRecipient<'info> {
+    pub fn update_reward_recipient(&mut self, new_recipient: Pubkey) -> Result<()> {
+        validate_reward_recipient(&self.study, &new_recipient)?;
+
+        if !self.submission.data_is_empty() {
+            let data = self.submission.try_borrow_data()?;
+            let submission = SubmissionAccount::try_deserialize(&mut &data[..])?;
+            require!(!submission.reward_distributed, RecruSearchError::RewardAlreadyDistributed);
+        }
+
+        // Pointing back at the participant's own wallet clears the override
+        let participant_key = self.participant.key();
+        let consent = &mut self.consent;
+        let old_state = consent.state();
+        consent.reward_recipient = if new_recipient == participant_key { None } else { Some(new_recipient) };
+
+        msg!("Reward recipient for {} set to {}", participant_key, new_recipient);
+
+        let timestamp = Clock::get()?.unix_timestamp;
+        emit!(RewardRecipientUpdated {
+            study_id: self.study.study_id,
+            participant: participant_key,
+            reward_recipient: new_recipient,
+            timestamp,
+        });
+        emit_consent_state_changed(&self.study, &self.consent, old_state, timestamp);
+
+        Ok(())
+    }
+}
+
+impl<'info> ReleaseCompletionBonus<'info> {
+    // Remaining accounts are (submission, consent, recipient token account) triples; each completed,
+    // unpaid submission receives an equal share fixed on the first release, paid to the consent's
+    // reward recipient. Participants who complete after the release are paid from whatever is left,
+    // so payouts never exceed the pool.
+    pub fn release_completion_bonus(&mut self, recipients: &'info [AccountInfo<'info>]) -> Result<u32> {
+        require!(
+            !recipients.is_empty()
+                && recipients.chunks_exact(3).remainder().is_empty()
+                && recipients.len() / 3 <= MAX_BATCH_SIZE,
+            RecruSearchError::BatchTooLarge
+        );
+
+        let study = &mut self.study;
+        require!(study.completion_bonus_pool > 0, RecruSearchError::InvalidParameterValue);
+        require!(completion_target_met(study), RecruSearchError::CompletionTargetNotMet);
+
+        // Fix the per-participant share once so later batches can't change it
+        if !study.completion_bonus_released {
+            study.completion_bonus_per_participant = study.completion_bonus_pool / study.completed_count as u64;
+            study.completion_bonus_released = true;
+
+            msg!("Completion bonus released: {} per participant", study.completion_bonus_per_participant);
+
+            emit!(CompletionBonusReleased {
+                study_id: study.study_id,
+                bonus_pool: study.completion_bonus_pool,
+                bonus_per_participant: study.completion_bonus_per_participant,
+                completed_count: study.completed_count,
+                timestamp: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        let study_key = study.key();
+        let share = study.completion_bonus_per_participant;
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study_key, self.reward_vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut paid_count: u32 = 0;
+        let mut total_paid: u64 = 0;
+        for triple in recipients.chunks_exact(3) {
+            require!(triple[0].is_writable, RecruSearchError::InvalidParameterValue);
+
+            let mut submission: Account<'info, SubmissionAccount> = Account::try_from(&triple[0])?;
+            require!(submission.study == study_key, RecruSearchError::InvalidParameterValue);
+
+            if submission.bonus_paid || !is_completed(&self.study, &submission) {
+                msg!("Skipping submission without an outstanding bonus: {}", triple[0].key());
+                continue;
+            }
+
+            let consent: Account<'info, ConsentAccount> = Account::try_from(&triple[1])?;
+            require!(
+                consent.study == study_key && consent.participant == submission.participant,
+                RecruSearchError::InvalidParameterValue
+            );
+
+            let remaining_pool = self
+                .study
+                .completion_bonus_pool
+                .saturating_sub(self.study.completion_bonus_paid_total);
+            let amount = share.min(remaining_pool);
+            if amount == 0 {
+                msg!("Completion bonus pool exhausted");
+                break;
+            }
+
+            let recipient_token_account: InterfaceAccount<'info, TokenAccount> = InterfaceAccount::try_from(&triple[2])?;
+            require!(
+                recipient_token_account.mint == self.reward_mint.key()
+                    && recipient_token_account.owner == consent.reward_recipient.unwrap_or(submission.participant),
+                RecruSearchError::TokenAccountMismatch
+            );
+
+            let cpi_accounts = TransferChecked {
+                from: self.vault_token_account.to_account_info(),
+                mint: self.reward_mint.to_account_info(),
+                to: triple[2].clone(),
+                authority: self.reward_vault.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new_with_signer(self.token_program.to_account_info(), cpi_accounts, signer_seeds);
+            transfer_checked(cpi_ctx, amount, self.reward_mint.decimals)?;
+
+            submission.bonus_paid = true;
+            submission.exit(&crate::ID)?;
+            record_payout(self.distribution_ledger.as_ref(), submission.participant, amount, now)?;
+            self.study.completion_bonus_paid_total += amount;
+            total_paid = total_paid
+                .checked_add(amount)
+                .ok_or(RecruSearchError::ArithmeticError)?;
+            paid_count += 1;
+        }
+
+        let vault = &mut self.reward_vault;
+        vault.total_distributed = vault.total_distributed.saturating_add(total_paid);
+        release_liability(vault, &mut self.admin_state, total_paid);
+        let study = &mut self.study;
+        study.total_rewards_distributed = study.total_rewards_distributed.saturating_add(total_paid);
+
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, now);
+
+        msg!("Paid completion bonus to {} participants in study {}", paid_count, study.study_id);
+
+        Ok(paid_count)
+    }
+}
+
+// Frees the reward reserved for a revoked participant; funded studies must pass their vault
+pub(crate) fn release_revoked_slot(
+    study: &StudyAccount,
+    reward_vault: Option<&mut Account<RewardVault>>,
+) -> Result<()> {
+    // A revoked participant's pool share simply goes to the remaining completers
+    if study.reward_vault.is_none() || study.pool_split_mode {
+        return Ok(());
+    }
+
+    let vault = reward_vault.ok_or(RecruSearchError::InvalidParameterValue)?;
+    vault.reclaimable_slots = vault.reclaimable_slots.saturating_add(1);
+
+    Ok(())
+}
+
+// Takes paid-out or withdrawn tokens off the protocol-wide liability, never more than the vault reserved
+pub(crate) fn release_liability(vault: &mut RewardVault, admin_state: &mut AdminAccount, amount: u64) {
+    let released = amount.min(vault.reserved_liability);
+    vault.reserved_liability -= released;
+    admin_state.total_active_liability = admin_state.total_active_liability.saturating_sub(released);
+}
+
+// Participants can claim until data collection ends plus the protocol's withdrawal grace period
+pub(crate) fn reward_claim_deadline(study: &StudyAccount, admin_state: &AdminAccount) -> i64 {
+    study
+        .data_collection_end
+        .saturating_add(admin_state.withdrawal_grace_period())
+}
+
+// Total tokens owed if every participant slot is rewarded, plus any completion bonus
+fn max_reward_liability(study: &StudyAccount) -> Result<u64> {
+    reward_liability(study, study.max_participants)
+}
+
+// Tokens owed if the given number of participants are rewarded, plus any completion bonus
+fn reward_liability(study: &StudyAccount, participants: u32) -> Result<u64> {
+    let base_liability = if study.pool_split_mode {
+        Some(study.reward_pool)
+    } else {
+        slot_liability(study).checked_mul(participants as u64)
+    };
+
+    base_liability
+        .and_then(|liability| liability.checked_add(study.completion_bonus_pool))
+        .ok_or(RecruSearchError::ArithmeticError.into())
+}
+
+// Vault balance not owed to enrolled participants, capped at what the token account holds
+pub(crate) fn unreserved_balance(study: &StudyAccount, vault: &RewardVault, vault_balance: u64) -> Result<u64> {
+    Ok(vault
+        .total_deposited
+        .saturating_sub(vault.total_distributed)
+        .saturating_sub(reward_liability(study, study.enrolled_count)?)
+        .min(vault_balance))
+}
+
+// Whether enough enrolled participants completed to unlock the completion bonus
+fn completion_target_met(study: &StudyAccount) -> bool {
+    study.enrolled_count > 0
+        && study.completed_count > 0
+        && study.completed_count as u64 * 10_000 / study.enrolled_count as u64 >= study.completion_target_bps as u64
+}
+
+// Tokens reserved for a single participant slot outside pool mode
+fn slot_liability(study: &StudyAccount) -> u64 {
+    study.reward_amount_per_participant.max(study.max_variable_reward)
+}
+
+// Even share of what is left in the pool across completers not yet paid.
+// submitted_count is final once the study closes, unlike completed_count which moves with payouts.
+fn pool_share(study: &StudyAccount) -> Result<u64> {
+    let unpaid_completers = study.submitted_count.saturating_sub(study.pool_paid_count);
+    require!(unpaid_completers > 0, RecruSearchError::InvalidParameterValue);
+
+    Ok(study.reward_pool.saturating_sub(study.pool_distributed) / unpaid_completers as u64)
+}
+
+// Payout wallets can't be the default address or, unless allowed, the researcher
+fn validate_reward_recipient(study: &StudyAccount, recipient: &Pubkey) -> Result<()> {
+    require!(*recipient != Pubkey::default(), RecruSearchError::InvalidParticipant);
+    require!(
+        study.allow_researcher_participation || *recipient != study.researcher,
+        RecruSearchError::SelfDealingNotAllowed
+    );
+
+    Ok(())
+}
+
+// Mirrors how completed_count is advanced for each reward type
+fn is_completed(study: &StudyAccount, submission: &SubmissionAccount) -> bool {
+    match study.reward_type {
+        RewardType::TokenOnly => submission.reward_distributed,
+        _ => submission.completion_nft_mint.is_some(),
+    }
+}
+
+// Rejects vault funding beyond the liability cap unless the researcher explicitly allows it
+fn validate_vault_funding(total_deposited: u64, max_liability: u64, allow_overfund: bool) -> Result<()> {
+    if allow_overfund {
+        return Ok(());
+    }
+
+    let funding_cap = (max_liability as u128 * MAX_VAULT_FUNDING_BPS as u128 / 10_000) as u64;
+    require!(
+        total_deposited <= funding_cap,
+        RecruSearchError::ExcessiveVaultFunding
+    );
+
+    Ok(())
+}
+
+// Helper function for vault signer seeds
+pub(crate) fn vault_signer_seeds(study_key: &Pubkey, vault_bump: u8) -> ([u8; 5], Vec<u8>, [u8; 1]) {
+    (b"vault".clone(), study_key.to_bytes().to_vec(), [vault_bump])
+}
+
+impl<'info> DistributeReward<'info> {
+    // Distributes reward tokens to participant after verification
+    pub fn distribute_reward(&mut self, bumps: &DistributeRewardBumps) -> Result<()> {
+        let study = &self.study;
+
+        // Fixed-amount studies pay while active or after closing; pool studies wait until the completer count is final
+        let reward_amount = if study.pool_split_mode {
+            require!(
+                study.status == StudyStatus::Closed,
+                RecruSearchError::PoolNotFinalized
+            );
+            require!(study.reward_pool > 0, RecruSearchError::InvalidRewardAmount);
+            pool_share(study)?
+        } else {
+            require!(
+                matches!(study.status, StudyStatus::Active | StudyStatus::Closed),
+                RecruSearchError::InvalidStudyState
+            );
+            study.reward_amount_per_participant
+        };
+
+        self.pay_reward(reward_amount, bumps)
+    }
+
+    // Pays a researcher-chosen amount, capped by the study's variable reward ceiling
+    pub fn distribute_variable_reward(&mut self, amount: u64, bumps: &DistributeRewardBumps) -> Result<()> {
+        let study = &self.study;
+
+        require!(!study.pool_split_mode, RecruSearchError::InvalidRewardType);
+        require!(
+            matches!(study.status, StudyStatus::Active | StudyStatus::Closed),
+            RecruSearchError::InvalidStudyState
+        );
+        require!(amount > 0, RecruSearchError::InvalidRewardAmount);
+        require!(
+            study.max_variable_reward > 0 && amount <= study.max_variable_reward,
+            RecruSearchError::VariableRewardAboveCeiling
+        );
+
+        self.pay_reward(amount, bumps)
+    }
+
+    // Shared payout path: recipient and timing checks, ATA creation, transfer and bookkeeping
+    fn pay_reward(&mut self, reward_amount: u64, bumps: &DistributeRewardBumps) -> Result<()> {
+        let study = &self.study;
+        let submission = &mut self.submission;
+        let vault = &mut self.reward_vault;
+
+        let clock = Clock::get()?;
+
+        require!(
+            study.reward_type != RewardType::NftOnly,
+            RecruSearchError::InvalidRewardType
+        );
+        require!(!study.is_reward_paused, RecruSearchError::RewardsPaused);
+
+        // Rate limit per researcher so a compromised key can't drain every vault at once
+        let researcher_profile = &mut self.researcher_profile;
+        if researcher_profile.researcher == Pubkey::default() {
+            researcher_profile.researcher = self.researcher.key();
+            researcher_profile.bump = bumps.researcher_profile;
+        }
+        require!(
+            researcher_profile.record_distribution(
+                clock.unix_timestamp,
+                self.admin_state.max_distributions_per_window,
+                self.admin_state.distribution_window_seconds,
+            ),
+            RecruSearchError::DistributionRateExceeded
+        );
+
+        // Block payouts to the default address and, unless allowed, to the researcher
+        let participant_key = self.participant.key();
+        validate_reward_recipient(study, &participant_key)?;
+        validate_reward_recipient(study, &self.reward_recipient.key())?;
+
+        // Enforce minimum time before claiming
+        if clock.unix_timestamp < submission.submission_timestamp + REWARD_CLAIM_DELAY {
+            return Err(study_error(study.study_id, RecruSearchError::ClaimTooEarly));
+        }
+
+        // The study decides which milestone counts as completion for payouts
+        match study.completion_requirement {
+            CompletionRequirement::OnSubmission => {}
+            CompletionRequirement::OnVerification => {
+                require!(submission.is_verified, RecruSearchError::SubmissionNotVerified);
+            }
+            CompletionRequirement::OnCompletionNFT => {
+                require!(
+                    submission.completion_nft_mint.is_some(),
+                    RecruSearchError::CompletionNftRequired
+                );
+            }
+        }
+
+        // Validate sufficient vault balance
+        let vault_token_balance = self.vault_token_account.amount;
+        if vault_token_balance < reward_amount {
+            return Err(study_error(study.study_id, RecruSearchError::InsufficientFunds));
+        }
+
+        // Create the participant ATA if needed, charging rent to whoever the study names
+        if self.participant_token_account.data_is_empty() {
+            let payer = match study.ata_rent_payer {
+                AtaRentPayer::Researcher => self.researcher.to_account_info(),
+                AtaRentPayer::Participant => self.participant.to_account_info(),
+            };
+
+            // Fail with a clear error rather than inside the ATA program when the payer is short
+            let ata_rent = Rent::get()?.minimum_balance(TOKEN_ACCOUNT_SIZE);
+            require!(payer.lamports() >= ata_rent, RecruSearchError::InsufficientRentBalance);
+
+            create_idempotent(CpiContext::new(
+                self.associated_token_program.to_account_info(),
+                Create {
+                    payer,
+                    associated_token: self.participant_token_account.to_account_info(),
+                    authority: self.reward_recipient.to_account_info(),
+                    mint: self.reward_mint.to_account_info(),
+                    system_program: self.system_program.to_account_info(),
+                    token_program: self.token_program.to_account_info(),
+                },
+            ))?;
+
+            msg!("Created recipient token account (rent paid by: {:?})", study.ata_rent_payer);
+        } else {
+            // An existing account must be a live token account for this mint owned by the recipient
+            require_keys_eq!(
+                *self.participant_token_account.owner,
+                self.token_program.key(),
+                RecruSearchError::TokenAccountMismatch
+            );
+            let data = self.participant_token_account.try_borrow_data()?;
+            let participant_token_account = TokenAccount::try_deserialize(&mut &data[..])
+                .map_err(|_| RecruSearchError::TokenAccountMismatch)?;
+            require_keys_eq!(
+                participant_token_account.mint,
+                self.reward_mint.key(),
+                RecruSearchError::TokenAccountMismatch
+            );
+            require_keys_eq!(
+                participant_token_account.owner,
+                self.reward_recipient.key(),
+                RecruSearchError::TokenAccountMismatch
+            );
+        }
+        
+        let (prefix, study_bytes, bump) = vault_signer_seeds(&study.key(), vault.bump);
+        let signer_seeds: &[&[u8]] = &[&prefix, &study_bytes, &bump];
+        let signer_seeds = &[signer_seeds];
+        
+        // Transfer tokens from vault to participant
+        let cpi_accounts = TransferChecked {
+            from: self.vault_token_account.to_account_info(),
+            mint: self.reward_mint.to_account_info(),
+            to: self.participant_token_account.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+
+        let cpi_program = self.token_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+
+        transfer_checked(
+            cpi_ctx,
+            reward_amount,
+            self.reward_mint.decimals,
+        )?;
+
+        vault.total_distributed = vault.total_distributed.saturating_add(reward_amount);
+        submission.reward_distributed = true;
+        submission.reward_amount = reward_amount;
+
+        let study = &mut self.study;
+        study.rewarded_count = study.rewarded_count.saturating_add(1);
+        study.total_rewards_distributed = study.total_rewards_distributed.saturating_add(reward_amount);
+        self.vault_token_account.reload()?;
+        study.cache_vault_balance(self.vault_token_account.amount, clock.unix_timestamp);
+        if study.pool_split_mode {
+            study.pool_distributed = study.pool_distributed.saturating_add(reward_amount);
+            study.pool_paid_count = study.pool_paid_count.saturating_add(1);
+        }
+
+        // Token-only studies never mint a completion NFT, so payout is what completes the participant
+        if study.reward_type == RewardType::TokenOnly {
+            study.completed_count = study.completed_count.saturating_add(1);
+            check_count_invariants(study)?;
+        }
+
+        let admin_state = &mut self.admin_state;
+        admin_state.total_rewards_distributed = admin_state.total_rewards_distributed.saturating_add(reward_amount);
+        release_liability(vault, admin_state, reward_amount);
+
+        record_payout(self.distribution_ledger.as_ref(), self.participant.key(), reward_amount, clock.unix_timestamp)?;
 
         msg!("Reward distributed successfully from vault");
         msg!("Amount: {} tokens", reward_amount);
@@ -287,6 +1643,7 @@ impl<'info> DistributeReward<'info> {
             study_id: study.study_id,
             participant: self.participant.key(),
             amount: reward_amount,
+            vault_remaining_balance: self.vault_token_account.amount,
             timestamp: clock.unix_timestamp,
         });
 
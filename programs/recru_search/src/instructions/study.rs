@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::instructions::rewards::reward_claim_deadline;
 use crate::state::*;
 
 #[derive(Accounts)]
@@ -27,6 +29,14 @@ pub struct CreateStudy<'info> {
     )]
     pub study: Account<'info, StudyAccount>,
 
+    // Protocol config - creation is blocked while the protocol is paused
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump,
+        constraint = !admin_state.is_paused @ RecruSearchError::ProtocolPaused
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
     // Only the researcher can create the study
     #[account(mut)]
     pub researcher: Signer<'info>,
@@ -44,11 +54,19 @@ pub struct PublishStudy<'info> {
         mut,
         seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
         constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
         constraint = study.status == StudyStatus::Draft @ RecruSearchError::InvalidStudyState
     )]
     pub study: Account<'info, StudyAccount>,
 
+    /// CHECK: reward vault PDA, may not exist yet - token-only studies must have it funded before publishing
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump
+    )]
+    pub reward_vault: UncheckedAccount<'info>,
+
     // Only the study researcher can publish
     #[account(mut)]
     pub researcher: Signer<'info>,
@@ -63,7 +81,9 @@ pub struct CloseStudy<'info> {
         mut,
         seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
         constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::StudyArchived,
         constraint = study.status != StudyStatus::Closed @ RecruSearchError::InvalidStudyState
     )]
     pub study: Account<'info, StudyAccount>,
@@ -73,6 +93,81 @@ pub struct CloseStudy<'info> {
     pub researcher: Signer<'info>,
 }
 
+// Study window extension - pushes enrollment and data collection deadlines out
+
+#[derive(Accounts)]
+pub struct ExtendStudyWindows<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Draft | StudyStatus::Published | StudyStatus::Active) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    pub researcher: Signer<'info>,
+
+    // Protocol admin co-signature, only needed above the extension threshold
+    pub protocol_admin: Option<Signer<'info>>,
+}
+
+// Study finalization - locks a wrapped-up study against any further change
+
+#[derive(Accounts)]
+pub struct FinalizeStudy<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Closed | StudyStatus::Archived) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    // Protocol config - supplies the grace period that bounds the reward claim window
+    #[account(
+        seeds = [b"admin"],
+        bump = admin_state.bump
+    )]
+    pub admin_state: Account<'info, AdminAccount>,
+
+    // Vault token account - required while the study still has a vault, which must be empty
+    #[account(
+        seeds = [b"vault_token", study.reward_vault.unwrap_or_default().as_ref()],
+        bump
+    )]
+    pub vault_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Study archival - retires a closed study once all follow-up is done
+
+#[derive(Accounts)]
+pub struct ArchiveStudy<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status == StudyStatus::Closed @ RecruSearchError::InvalidStatusTransition
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+}
+
 // Study state transition -handles automatic state changes based on time
 
 #[derive(Accounts)]
@@ -80,10 +175,211 @@ pub struct TransitionStudyState<'info> {
     // Study account for state transition
     #[account(
         mut,
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized
+    )]
+    pub study: Account<'info, StudyAccount>,
+}
+
+// Batch state transition - keepers pass study accounts in remaining accounts
+
+#[derive(Accounts)]
+pub struct TransitionStudiesBatch<'info> {
+    pub keeper: Signer<'info>,
+}
+
+// Enrollment projection - read-only ETA for filling the study
+
+#[derive(Accounts)]
+pub struct GetEnrollmentProjection<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+}
+
+// Study timeline - read-only view of every lifecycle timestamp
+
+#[derive(Accounts)]
+pub struct GetStudyTimeline<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+}
+
+// Remaining slots - read-only capacity check for enrollment UIs
+
+#[derive(Accounts)]
+pub struct GetRemainingSlots<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+}
+
+// Study info - read-only summary of enrollment and churn counters
+
+#[derive(Accounts)]
+pub struct GetStudyInfo<'info> {
+    #[account(
+        seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump
+    )]
+    pub study: Account<'info, StudyAccount>,
+}
+
+// Study counters - one read for the researcher overview screen
+
+#[derive(Accounts)]
+pub struct GetStudyCounters<'info> {
+    #[account(
         seeds = [b"study", study.researcher.as_ref(), study.study_id.to_le_bytes().as_ref()],
         bump = study.bump
     )]
     pub study: Account<'info, StudyAccount>,
+
+    // Optional - studies without a vault or survey schema report zeros
+    #[account(
+        seeds = [b"vault", study.key().as_ref()],
+        bump = reward_vault.bump
+    )]
+    pub reward_vault: Option<Account<'info, RewardVault>>,
+
+    #[account(
+        seeds = [b"data_stats", study.key().as_ref()],
+        bump = data_stats.bump
+    )]
+    pub data_stats: Option<Account<'info, DataCollectionStats>>,
+}
+
+// Reward amount correction - only while the study is a draft with no vault sized from it
+
+#[derive(Accounts)]
+pub struct UpdateRewardAmount<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status == StudyStatus::Draft @ RecruSearchError::InvalidStudyState,
+        constraint = study.reward_vault.is_none() @ RecruSearchError::VaultAlreadyExists
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Study metadata - extended description and protocol documents beyond the on-study limits
+
+#[derive(Accounts)]
+pub struct SetStudyMetadata<'info> {
+    #[account(
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status != StudyStatus::Archived @ RecruSearchError::StudyArchived
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    // init_if_needed so the researcher can revise the metadata later
+    #[account(
+        init_if_needed,
+        payer = researcher,
+        space = 8 + StudyMetadata::INIT_SPACE,
+        seeds = [b"metadata", study.key().as_ref()],
+        bump
+    )]
+    pub study_metadata: Account<'info, StudyMetadata>,
+
+    #[account(mut)]
+    pub researcher: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// IRB approval - researcher records the ethics approval reference, renewable until the study closes
+
+#[derive(Accounts)]
+pub struct SetIrbApproval<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = matches!(study.status, StudyStatus::Draft | StudyStatus::Published | StudyStatus::Active) @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Notification config - reference to an off-chain webhook config, changeable at any stage
+
+#[derive(Accounts)]
+pub struct SetNotificationConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Study settings - optional configuration, editable while the study is still a draft
+
+#[derive(Accounts)]
+pub struct UpdateStudySettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"study", researcher.key().as_ref(), study.study_id.to_le_bytes().as_ref()],
+        bump = study.bump,
+        constraint = !study.is_finalized @ RecruSearchError::StudyFinalized,
+        constraint = study.researcher == researcher.key() @ RecruSearchError::UnauthorizedResearcher,
+        constraint = study.status == StudyStatus::Draft @ RecruSearchError::InvalidStudyState
+    )]
+    pub study: Account<'info, StudyAccount>,
+
+    pub researcher: Signer<'info>,
+}
+
+// Fields left as None keep their current value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct StudySettings {
+    pub ata_rent_payer: Option<AtaRentPayer>,
+    pub allow_researcher_participation: Option<bool>,
+    pub reward_type: Option<RewardType>,
+    // Pubkey::default() clears the oracle
+    pub age_oracle: Option<Pubkey>,
+    pub requires_unique_identity: Option<bool>,
+    pub completion_bonus_pool: Option<u64>,
+    pub completion_target_bps: Option<u16>,
+    pub submissions_per_participant: Option<u32>,
+    pub completion_nft_cap: Option<u32>,
+    pub pool_split_mode: Option<bool>,
+    pub reward_pool: Option<u64>,
+    pub requires_irb_approval: Option<bool>,
+    // Zero disables variable rewards
+    pub max_variable_reward: Option<u64>,
+    pub detect_duplicate_hashes: Option<bool>,
+    pub completion_requirement: Option<CompletionRequirement>,
+    // Completion certificates only go to verified submissions
+    pub requires_verification: Option<bool>,
+    // Revoked consent NFTs are marked revoked instead of burned, keeping the historical certificate
+    pub preserve_revoked_nft: Option<bool>,
+    // Bitmask of EncryptionScheme values; must allow at least one known scheme
+    pub allowed_encryption_schemes: Option<u8>,
 }
 
 impl<'info> CreateStudy<'info> {
@@ -103,8 +399,15 @@ impl<'info> CreateStudy<'info> {
         let study = &mut self.study;
         let clock = Clock::get()?;
 
+        // Sanity guard checked first so a study can never be born already expired
+        require!(data_collection_end > clock.unix_timestamp, RecruSearchError::InvalidDataCollectionEnd);
+
         // Validate enrollment start time
         require!(enrollment_start > clock.unix_timestamp, RecruSearchError::InvalidEnrollmentStart);
+        require!(
+            enrollment_start <= clock.unix_timestamp.saturating_add(MAX_ENROLLMENT_LEAD_TIME),
+            RecruSearchError::EnrollmentStartTooFar
+        );
         
         // Validate enrollment period duration
         let enrollment_duration = enrollment_end - enrollment_start;
@@ -113,6 +416,12 @@ impl<'info> CreateStudy<'info> {
             RecruSearchError::InvalidEnrollmentPeriod
         );
 
+        // Leave participants a realistic window to submit after enrollment closes
+        require!(
+            data_collection_end - enrollment_end >= MIN_DATA_COLLECTION_WINDOW,
+            RecruSearchError::DataCollectionWindowTooShort
+        );
+
         // Validate total study duration
         let total_duration = data_collection_end - enrollment_start;
         require!(
@@ -132,12 +441,58 @@ impl<'info> CreateStudy<'info> {
         study.reward_amount_per_participant = reward_amount;
         study.enrolled_count = 0;
         study.completed_count = 0;
+        study.submitted_count = 0;
+        study.total_revocations = 0;
         study.status = StudyStatus::Draft;
         study.created_at = clock.unix_timestamp;
+        study.published_at = None;
+        study.closed_at = None;
+        study.archived_at = None;
 
         // Initialize eligibility criteria fields
         study.has_eligibility_criteria = false;
         study.eligibility_criteria = Vec::new();
+        study.criteria_version = 0;
+        study.criteria_locked = false;
+        study.reward_merkle_root = None;
+        study.invite_merkle_root = None;
+        study.rating_sum = 0;
+        study.rating_count = 0;
+        study.completion_bonus_pool = 0;
+        study.completion_target_bps = 0;
+        study.completion_bonus_per_participant = 0;
+        study.completion_bonus_released = false;
+        study.completion_bonus_paid_total = 0;
+        study.submissions_per_participant = 1;
+        study.completion_nft_cap = None;
+        study.pool_split_mode = false;
+        study.reward_pool = 0;
+        study.pool_distributed = 0;
+        study.pool_paid_count = 0;
+        study.rewarded_count = 0;
+        study.has_survey_schema = false;
+        study.survey_finalized = false;
+        study.requires_irb_approval = false;
+        study.irb_approval_hash = [0u8; 32];
+        study.irb_approval_expires = 0;
+        study.max_variable_reward = 0;
+        study.detect_duplicate_hashes = false;
+        study.completion_requirement = CompletionRequirement::OnSubmission;
+        study.requires_verification = false;
+        study.preserve_revoked_nft = false;
+        study.is_finalized = false;
+        study.allowed_encryption_schemes = EncryptionScheme::ALL;
+        study.encrypted_responses = 0;
+        study.notification_config_hash = None;
+        study.last_known_vault_balance = 0;
+        study.vault_balance_updated_at = 0;
+        study.ata_rent_payer = AtaRentPayer::Participant;
+        study.is_reward_paused = false;
+        study.allow_researcher_participation = false;
+        study.reward_type = RewardType::TokenAndNft;
+        study.reward_vault = None;
+        study.age_oracle = None;
+        study.requires_unique_identity = false;
         study.bump = bumps.study;
         study.total_rewards_distributed = 0;
 
@@ -161,14 +516,274 @@ impl<'info> CreateStudy<'info> {
     }
 }
 
+impl<'info> UpdateRewardAmount<'info> {
+    pub fn update_reward_amount(&mut self, new_amount: u64) -> Result<()> {
+        require!(new_amount > 0, RecruSearchError::InvalidRewardAmount);
+
+        let study = &mut self.study;
+        let old_amount = study.reward_amount_per_participant;
+        study.reward_amount_per_participant = new_amount;
+
+        msg!("Study {} reward amount updated: {} -> {}", study.study_id, old_amount, new_amount);
+
+        emit!(RewardAmountUpdated {
+            study_id: study.study_id,
+            old_amount,
+            new_amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetIrbApproval<'info> {
+    // Records the approval document hash and when the approval lapses
+    pub fn set_irb_approval(&mut self, approval_hash: [u8; 32], expires_at: i64) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(approval_hash != [0u8; 32], RecruSearchError::InvalidParameterValue);
+        require!(expires_at > clock.unix_timestamp, RecruSearchError::IrbApprovalExpired);
+
+        let study = &mut self.study;
+        study.irb_approval_hash = approval_hash;
+        study.irb_approval_expires = expires_at;
+
+        msg!("IRB approval recorded for study {} until {}", study.study_id, expires_at);
+
+        emit!(IrbApprovalSet {
+            study_id: study.study_id,
+            approval_hash,
+            expires_at,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetNotificationConfig<'info> {
+    // Records the config hash indexers use to route webhooks; None clears it
+    pub fn set_notification_config(&mut self, config_hash: Option<[u8; 32]>) -> Result<()> {
+        require!(config_hash != Some([0u8; 32]), RecruSearchError::InvalidParameterValue);
+
+        let study = &mut self.study;
+        study.notification_config_hash = config_hash;
+
+        msg!("Notification config for study {}: {:?}", study.study_id, config_hash);
+
+        emit!(NotificationConfigUpdated {
+            study_id: study.study_id,
+            notification_config_hash: config_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> SetStudyMetadata<'info> {
+    // Stores the long-form description and protocol document CID; an empty CID means none
+    pub fn set_study_metadata(
+        &mut self,
+        extended_description: String,
+        protocol_ipfs_cid: String,
+        bumps: &SetStudyMetadataBumps,
+    ) -> Result<()> {
+        require!(
+            extended_description.len() <= MAX_EXTENDED_DESCRIPTION_LENGTH,
+            RecruSearchError::ExtendedDescriptionTooLong
+        );
+        require!(
+            protocol_ipfs_cid.is_empty() || (protocol_ipfs_cid.len() >= 10 && protocol_ipfs_cid.len() <= 100),
+            RecruSearchError::InvalidIPFSCID
+        );
+
+        let clock = Clock::get()?;
+        let metadata = &mut self.study_metadata;
+        metadata.study = self.study.key();
+        metadata.extended_description = extended_description;
+        metadata.protocol_ipfs_cid = protocol_ipfs_cid.clone();
+        metadata.updated_at = clock.unix_timestamp;
+        metadata.bump = bumps.study_metadata;
+
+        msg!("Metadata updated for study {}", self.study.study_id);
+
+        emit!(StudyMetadataUpdated {
+            study_id: self.study.study_id,
+            protocol_ipfs_cid,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> UpdateStudySettings<'info> {
+    // Applies the provided settings to a draft study
+    pub fn update_study_settings(&mut self, settings: StudySettings) -> Result<()> {
+        let study = &mut self.study;
+
+        // Vault liability and the funding cap are sized from these when the vault is created
+        if study.reward_vault.is_some() {
+            let changes_liability = settings.pool_split_mode.is_some_and(|value| value != study.pool_split_mode)
+                || settings.reward_pool.is_some_and(|value| value != study.reward_pool)
+                || settings.max_variable_reward.is_some_and(|value| value != study.max_variable_reward)
+                || settings.completion_bonus_pool.is_some_and(|value| value != study.completion_bonus_pool);
+            require!(!changes_liability, RecruSearchError::VaultAlreadyExists);
+        }
+
+        if let Some(ata_rent_payer) = settings.ata_rent_payer {
+            study.ata_rent_payer = ata_rent_payer;
+            msg!("ATA rent payer: {:?}", ata_rent_payer);
+        }
+
+        if let Some(allow_researcher_participation) = settings.allow_researcher_participation {
+            study.allow_researcher_participation = allow_researcher_participation;
+            msg!("Allow researcher participation: {}", allow_researcher_participation);
+        }
+
+        if let Some(reward_type) = settings.reward_type {
+            study.reward_type = reward_type;
+            msg!("Reward type: {:?}", reward_type);
+        }
+
+        if let Some(age_oracle) = settings.age_oracle {
+            study.age_oracle = if age_oracle == Pubkey::default() { None } else { Some(age_oracle) };
+            msg!("Age oracle: {:?}", study.age_oracle);
+        }
+
+        if let Some(requires_unique_identity) = settings.requires_unique_identity {
+            study.requires_unique_identity = requires_unique_identity;
+            msg!("Requires unique identity: {}", requires_unique_identity);
+        }
+
+        if let Some(completion_bonus_pool) = settings.completion_bonus_pool {
+            study.completion_bonus_pool = completion_bonus_pool;
+            msg!("Completion bonus pool: {}", completion_bonus_pool);
+        }
+
+        if let Some(completion_target_bps) = settings.completion_target_bps {
+            require!(completion_target_bps <= 10_000, RecruSearchError::InvalidParameterValue);
+            study.completion_target_bps = completion_target_bps;
+            msg!("Completion target: {} bps", completion_target_bps);
+        }
+
+        if let Some(submissions_per_participant) = settings.submissions_per_participant {
+            require!(
+                (1..=MAX_SUBMISSIONS_PER_PARTICIPANT).contains(&submissions_per_participant),
+                RecruSearchError::InvalidParameterValue
+            );
+            study.submissions_per_participant = submissions_per_participant;
+            msg!("Submissions per participant: {}", submissions_per_participant);
+        }
+
+        // Zero clears the cap back to one certificate per participant slot
+        if let Some(completion_nft_cap) = settings.completion_nft_cap {
+            require!(completion_nft_cap <= study.max_participants, RecruSearchError::InvalidParameterValue);
+            study.completion_nft_cap = if completion_nft_cap == 0 { None } else { Some(completion_nft_cap) };
+            msg!("Completion NFT cap: {:?}", study.completion_nft_cap);
+        }
+
+        if let Some(pool_split_mode) = settings.pool_split_mode {
+            study.pool_split_mode = pool_split_mode;
+            msg!("Pool split mode: {}", pool_split_mode);
+        }
+
+        if let Some(reward_pool) = settings.reward_pool {
+            study.reward_pool = reward_pool;
+            msg!("Reward pool: {}", reward_pool);
+        }
+
+        if let Some(requires_irb_approval) = settings.requires_irb_approval {
+            study.requires_irb_approval = requires_irb_approval;
+            msg!("Requires IRB approval: {}", requires_irb_approval);
+        }
+
+        if let Some(max_variable_reward) = settings.max_variable_reward {
+            study.max_variable_reward = max_variable_reward;
+            msg!("Max variable reward: {}", max_variable_reward);
+        }
+
+        if let Some(detect_duplicate_hashes) = settings.detect_duplicate_hashes {
+            study.detect_duplicate_hashes = detect_duplicate_hashes;
+            msg!("Detect duplicate data hashes: {}", detect_duplicate_hashes);
+        }
+
+        if let Some(completion_requirement) = settings.completion_requirement {
+            study.completion_requirement = completion_requirement;
+            msg!("Completion requirement: {:?}", completion_requirement);
+        }
+
+        if let Some(requires_verification) = settings.requires_verification {
+            study.requires_verification = requires_verification;
+            msg!("Requires verification: {}", requires_verification);
+        }
+
+        if let Some(preserve_revoked_nft) = settings.preserve_revoked_nft {
+            study.preserve_revoked_nft = preserve_revoked_nft;
+            msg!("Preserve revoked NFTs: {}", preserve_revoked_nft);
+        }
+
+        if let Some(allowed_encryption_schemes) = settings.allowed_encryption_schemes {
+            require!(
+                allowed_encryption_schemes != 0 && allowed_encryption_schemes & !EncryptionScheme::ALL == 0,
+                RecruSearchError::InvalidParameterValue
+            );
+            study.allowed_encryption_schemes = allowed_encryption_schemes;
+            msg!("Allowed encryption schemes: {:#06b}", allowed_encryption_schemes);
+        }
+
+        // Token-only studies never mint completion NFTs, so that requirement could never be met
+        require!(
+            !(study.reward_type == RewardType::TokenOnly
+                && study.completion_requirement == CompletionRequirement::OnCompletionNFT),
+            RecruSearchError::InvalidRewardType
+        );
+
+        // Participants past a completion NFT cap could never mint, so they'd never be paid
+        require!(
+            !(study.completion_requirement == CompletionRequirement::OnCompletionNFT
+                && study.completion_nft_limit() < study.max_participants),
+            RecruSearchError::InvalidParameterValue
+        );
+
+        msg!("Study settings updated for study: {}", study.study_id);
+
+        Ok(())
+    }
+}
+
 impl<'info> PublishStudy<'info> {
     // Publishes a draft study to make it available for enrollment
     pub fn publish_study(&mut self) -> Result<()> {
-        let study = &mut self.study;
         let clock = Clock::get()?;
+
+        // Regulated studies can't open enrollment without a current ethics approval
+        if self.study.requires_irb_approval {
+            require!(self.study.irb_approval_hash != [0u8; 32], RecruSearchError::IrbApprovalMissing);
+            require!(
+                self.study.has_valid_irb_approval(clock.unix_timestamp),
+                RecruSearchError::IrbApprovalExpired
+            );
+        }
+
+        // Token-only participants have nothing but the payout, so it must be backed before enrollment opens
+        if self.study.reward_type == RewardType::TokenOnly {
+            require!(!self.reward_vault.data_is_empty(), RecruSearchError::VaultNotFunded);
+
+            let data = self.reward_vault.try_borrow_data()?;
+            let vault = RewardVault::try_deserialize(&mut &data[..])?;
+            require!(
+                vault.total_deposited.saturating_sub(vault.total_distributed) >= self.study.reward_amount_per_participant,
+                RecruSearchError::VaultNotFunded
+            );
+        }
+
+        let study = &mut self.study;
         
         // Change status to published
         study.status = StudyStatus::Published;
+        study.published_at = Some(clock.unix_timestamp);
         
         // Log publication details
         msg!("Study published: {} at timestamp: {}", study.study_id, clock.unix_timestamp);
@@ -179,6 +794,7 @@ impl<'info> PublishStudy<'info> {
         emit!(StudyPublished {
             study_id: study.study_id,
             researcher: self.researcher.key(),
+            notification_config_hash: study.notification_config_hash,
         });
         
         Ok(())
@@ -193,6 +809,7 @@ impl<'info> CloseStudy<'info> {
         
         // Change status to closed
         study.status = StudyStatus::Closed;
+        study.closed_at = Some(clock.unix_timestamp);
         
         // Log closure details
         msg!("Study closed: {} at timestamp: {}", study.study_id, clock.unix_timestamp);
@@ -204,13 +821,265 @@ impl<'info> CloseStudy<'info> {
             study_id: study.study_id,
             researcher: self.researcher.key(),
             total_participants: study.enrolled_count,
-            total_submissions: study.completed_count,
+            total_submissions: study.submitted_count,
+            total_revocations: study.total_revocations,
+            completed_count: study.completed_count,
+            notification_config_hash: study.notification_config_hash,
         });
         
         Ok(())
     }
 }
 
+impl<'info> ExtendStudyWindows<'info> {
+    // Extends study deadlines; large extensions need the protocol admin to co-sign
+    pub fn extend_study_windows(&mut self, new_enrollment_end: i64, new_data_collection_end: i64) -> Result<()> {
+        let study = &mut self.study;
+
+        // Windows can only move later
+        require!(new_enrollment_end >= study.enrollment_end, RecruSearchError::InvalidEnrollmentEnd);
+        require!(
+            new_data_collection_end >= study.data_collection_end,
+            RecruSearchError::InvalidDataCollectionEnd
+        );
+        require!(
+            new_data_collection_end - new_enrollment_end >= MIN_DATA_COLLECTION_WINDOW,
+            RecruSearchError::DataCollectionWindowTooShort
+        );
+        require!(
+            new_data_collection_end - study.enrollment_start <= MAX_STUDY_DURATION,
+            RecruSearchError::InvalidDataCollectionPeriod
+        );
+
+        let requested_extension = (new_enrollment_end - study.enrollment_end)
+            .max(new_data_collection_end - study.data_collection_end);
+        let threshold = self.admin_state.extension_requires_admin_above_seconds;
+        let protocol_admin = self.admin_state.protocol_admin;
+
+        let admin_approved = match &self.protocol_admin {
+            Some(signer) => {
+                require_keys_eq!(signer.key(), protocol_admin, RecruSearchError::UnauthorizedAccess);
+                true
+            }
+            None => false,
+        };
+
+        if requested_extension > threshold && !admin_approved {
+            msg!("Extension of {} seconds exceeds {} - resubmit with the protocol admin as co-signer", requested_extension, threshold);
+            emit!(ExtensionApprovalRequired {
+                study_id: study.study_id,
+                requested_extension,
+                threshold,
+                protocol_admin,
+            });
+            return Err(RecruSearchError::AdminApprovalRequired.into());
+        }
+
+        study.enrollment_end = new_enrollment_end;
+        study.data_collection_end = new_data_collection_end;
+
+        msg!("Study {} windows extended by up to {} seconds", study.study_id, requested_extension);
+
+        emit!(StudyWindowsExtended {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            enrollment_end: new_enrollment_end,
+            data_collection_end: new_data_collection_end,
+            admin_approved,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> FinalizeStudy<'info> {
+    // Locks the study once every remaining participant has completed
+    pub fn finalize_study(&mut self) -> Result<()> {
+        let study = &mut self.study;
+
+        // enrolled_count already excludes revoked participants
+        require!(
+            study.completed_count >= study.enrolled_count,
+            RecruSearchError::StudyHasIncompleteParticipants
+        );
+
+        // Submitted participants keep their claim until they are paid or the claim window closes
+        let clock = Clock::get()?;
+        if study.reward_type != RewardType::NftOnly {
+            require!(
+                study.rewarded_count >= study.submitted_count
+                    || clock.unix_timestamp > reward_claim_deadline(study, &self.admin_state),
+                RecruSearchError::UnpaidSubmissionsRemain
+            );
+        }
+
+        // Withdrawals are blocked once finalized, so an open vault has to be empty already
+        if study.reward_vault.is_some() {
+            let vault_balance = self
+                .vault_token_account
+                .as_ref()
+                .map(|vault_token_account| vault_token_account.amount)
+                .ok_or(RecruSearchError::RewardVaultNotEmpty)?;
+            require!(vault_balance == 0, RecruSearchError::RewardVaultNotEmpty);
+        }
+
+        study.is_finalized = true;
+
+        msg!("Study finalized: {} at timestamp: {}", study.study_id, clock.unix_timestamp);
+
+        emit!(StudyFinalized {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            completed_count: study.completed_count,
+            total_revocations: study.total_revocations,
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> ArchiveStudy<'info> {
+    // Moves a closed study into the terminal Archived state
+    pub fn archive_study(&mut self) -> Result<()> {
+        let study = &mut self.study;
+        let clock = Clock::get()?;
+
+        study.status = StudyStatus::Archived;
+        study.archived_at = Some(clock.unix_timestamp);
+
+        msg!("Study archived: {} at timestamp: {}", study.study_id, clock.unix_timestamp);
+
+        emit!(StudyArchived {
+            study_id: study.study_id,
+            researcher: self.researcher.key(),
+            timestamp: clock.unix_timestamp,
+        });
+
+        Ok(())
+    }
+}
+
+impl<'info> GetRemainingSlots<'info> {
+    // Open means a consent mint would pass the status, window and capacity checks
+    pub fn get_remaining_slots(&self) -> Result<RemainingSlots> {
+        let study = &self.study;
+        let now = Clock::get()?.unix_timestamp;
+
+        let remaining = study.max_participants.saturating_sub(study.enrolled_count);
+        let is_open = study.status == StudyStatus::Published
+            && now >= study.enrollment_start
+            && now <= study.enrollment_end
+            && remaining > 0;
+
+        Ok(RemainingSlots { remaining, is_open })
+    }
+}
+
+impl<'info> GetStudyInfo<'info> {
+    // Headline counters for dashboards and churn analytics
+    pub fn get_study_info(&self) -> Result<StudyInfo> {
+        let study = &self.study;
+        let now = Clock::get()?.unix_timestamp;
+
+        Ok(StudyInfo {
+            study_id: study.study_id,
+            researcher: study.researcher,
+            title: study.title.clone(),
+            status: study.status.clone(),
+            max_participants: study.max_participants,
+            enrolled_count: study.enrolled_count,
+            submitted_count: study.submitted_count,
+            completed_count: study.completed_count,
+            total_revocations: study.total_revocations,
+            reward_amount_per_participant: study.reward_amount_per_participant,
+            total_rewards_distributed: study.total_rewards_distributed,
+            requires_irb_approval: study.requires_irb_approval,
+            irb_approval_valid: study.has_valid_irb_approval(now),
+            irb_approval_expires: study.irb_approval_expires,
+            last_known_vault_balance: study.last_known_vault_balance,
+            vault_balance_updated_at: study.vault_balance_updated_at,
+        })
+    }
+}
+
+impl<'info> GetStudyCounters<'info> {
+    // Every headline counter in one call so dashboards skip the extra round-trips
+    pub fn get_study_counters(&self) -> Result<StudyCounters> {
+        let study = &self.study;
+
+        let vault_available = self.reward_vault.as_ref().map_or(0, |vault| {
+            vault.total_deposited.saturating_sub(vault.total_distributed)
+        });
+        let (total_responses, validated_responses) = self
+            .data_stats
+            .as_ref()
+            .map_or((0, 0), |stats| (stats.total_responses, stats.validated_responses));
+
+        Ok(StudyCounters {
+            enrolled_count: study.enrolled_count,
+            completed_count: study.completed_count,
+            submitted_count: study.submitted_count,
+            total_revocations: study.total_revocations,
+            total_rewards_distributed: study.total_rewards_distributed,
+            remaining_slots: study.max_participants.saturating_sub(study.enrolled_count),
+            vault_available,
+            total_responses,
+            validated_responses,
+        })
+    }
+}
+
+impl<'info> GetEnrollmentProjection<'info> {
+    // Projects when the study reaches max_participants at the current enrollment rate
+    pub fn get_enrollment_projection(&self) -> Result<EnrollmentProjection> {
+        let study = &self.study;
+        let now = Clock::get()?.unix_timestamp;
+
+        let start = study.enrollment_start.max(study.created_at);
+        let elapsed = now.saturating_sub(start).max(1) as i128;
+        let enrolled = study.enrolled_count as i128;
+        let remaining = study.max_participants.saturating_sub(study.enrolled_count) as i128;
+
+        let enrollments_per_day = (enrolled * SECONDS_PER_DAY as i128 / elapsed) as u64;
+
+        // Without any enrollments there is no rate to extrapolate from
+        let (projected_full_date, will_fill_in_time) = if remaining == 0 {
+            (now, true)
+        } else if enrolled == 0 {
+            (study.enrollment_end, false)
+        } else {
+            let projected = now as i128 + remaining * elapsed / enrolled;
+            let will_fill = projected <= study.enrollment_end as i128;
+            (projected.min(study.enrollment_end as i128) as i64, will_fill)
+        };
+
+        Ok(EnrollmentProjection {
+            enrollments_per_day,
+            projected_full_date,
+            will_fill_in_time,
+        })
+    }
+}
+
+impl<'info> GetStudyTimeline<'info> {
+    // Collects the scheduled windows and recorded transitions in one place
+    pub fn get_study_timeline(&self) -> Result<StudyTimeline> {
+        let study = &self.study;
+
+        Ok(StudyTimeline {
+            created_at: study.created_at,
+            enrollment_start: study.enrollment_start,
+            enrollment_end: study.enrollment_end,
+            data_collection_end: study.data_collection_end,
+            published_at: study.published_at,
+            closed_at: study.closed_at,
+            archived_at: study.archived_at,
+            current_phase: study.status.clone(),
+        })
+    }
+}
+
 impl<'info> TransitionStudyState<'info> {
     // Handles automatic state transitions based on time conditions
     pub fn transition_study_state(&mut self) -> Result<()> {
@@ -222,11 +1091,7 @@ impl<'info> TransitionStudyState<'info> {
         // Check for automatic transitions based on current state and time
         match study.status {
             StudyStatus::Published => {
-                // Auto-transition to Active when data collection period starts
-                if current_time >= study.data_collection_end {
-                    study.status = StudyStatus::Active;
-                    msg!("Study transitioned to Active state");
-                }
+                apply_time_transition(study, current_time);
             },
             StudyStatus::Active => {
                 // Manual transition to Closed via close_study
@@ -238,4 +1103,119 @@ impl<'info> TransitionStudyState<'info> {
         
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl<'info> TransitionStudiesBatch<'info> {
+    // Transitions each ready study, skipping ones with nothing pending instead of failing the batch
+    pub fn transition_studies_batch(&mut self, studies: &'info [AccountInfo<'info>]) -> Result<u32> {
+        require!(
+            !studies.is_empty() && studies.len() <= MAX_BATCH_SIZE,
+            RecruSearchError::BatchTooLarge
+        );
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let mut transitioned_count: u32 = 0;
+
+        for info in studies.iter() {
+            require!(info.is_writable, RecruSearchError::InvalidParameterValue);
+
+            let mut study: Account<'info, StudyAccount> = Account::try_from(info)?;
+            if !apply_time_transition(&mut study, current_time) {
+                msg!("No pending transition, skipping: {}", info.key());
+                continue;
+            }
+
+            study.exit(&crate::ID)?;
+            transitioned_count += 1;
+        }
+
+        msg!("Transitioned {} of {} studies", transitioned_count, studies.len());
+
+        Ok(transitioned_count)
+    }
+}
+
+// Checked after every participant count change. Revoked participants leave enrolled_count but may
+// already have submitted or completed, so counts are bounded by everyone who ever enrolled.
+pub(crate) fn check_count_invariants(study: &StudyAccount) -> Result<()> {
+    let ever_enrolled = study.enrolled_count as u64 + study.total_revocations as u64;
+    require!(
+        study.enrolled_count <= study.max_participants
+            && study.submitted_count as u64 <= ever_enrolled
+            && study.completed_count as u64 <= ever_enrolled,
+        RecruSearchError::CountInvariantViolated
+    );
+
+    Ok(())
+}
+
+// Published studies become Active once data collection has ended; returns whether the study changed
+fn apply_time_transition(study: &mut StudyAccount, current_time: i64) -> bool {
+    if study.status == StudyStatus::Published && current_time >= study.data_collection_end {
+        study.status = StudyStatus::Active;
+        msg!("Study {} transitioned to Active state", study.study_id);
+        return true;
+    }
+
+    false
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StudyInfo {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub title: String,
+    pub status: StudyStatus,
+    pub max_participants: u32,
+    pub enrolled_count: u32,
+    pub submitted_count: u32,
+    pub completed_count: u32,
+    pub total_revocations: u32,
+    pub reward_amount_per_participant: u64,
+    pub total_rewards_distributed: u64,
+    pub requires_irb_approval: bool,
+    pub irb_approval_valid: bool,
+    pub irb_approval_expires: i64,
+    // Cached at the last vault-affecting instruction and may lag direct transfers
+    pub last_known_vault_balance: u64,
+    pub vault_balance_updated_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StudyCounters {
+    pub enrolled_count: u32,
+    pub completed_count: u32,
+    pub submitted_count: u32,
+    pub total_revocations: u32,
+    pub total_rewards_distributed: u64,
+    pub remaining_slots: u32,
+    // Deposited minus distributed; zero when the study has no vault
+    pub vault_available: u64,
+    pub total_responses: u32,
+    pub validated_responses: u32,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct RemainingSlots {
+    pub remaining: u32,
+    pub is_open: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct EnrollmentProjection {
+    pub enrollments_per_day: u64,
+    pub projected_full_date: i64,
+    pub will_fill_in_time: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StudyTimeline {
+    pub created_at: i64,
+    pub enrollment_start: i64,
+    pub enrollment_end: i64,
+    pub data_collection_end: i64,
+    pub published_at: Option<i64>,
+    pub closed_at: Option<i64>,
+    pub archived_at: Option<i64>,
+    pub current_phase: StudyStatus,
+}
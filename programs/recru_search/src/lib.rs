@@ -20,11 +20,65 @@ pub mod recru_search {
         Ok(())
     }
 
+    pub fn set_ipfs_gateway(ctx: Context<SetIpfsGateway>, ipfs_gateway: String) -> Result<()> {
+        ctx.accounts.set_ipfs_gateway(ipfs_gateway)?;
+        Ok(())
+    }
+
+    pub fn set_extension_approval_threshold(ctx: Context<SetExtensionApprovalThreshold>, threshold_seconds: i64) -> Result<()> {
+        ctx.accounts.set_extension_approval_threshold(threshold_seconds)?;
+        Ok(())
+    }
+
+    pub fn set_grace_periods(ctx: Context<SetGracePeriods>, withdrawal: Option<i64>, retention: Option<i64>) -> Result<()> {
+        ctx.accounts.set_grace_periods(withdrawal, retention)?;
+        Ok(())
+    }
+
+    pub fn set_distribution_rate_limit(ctx: Context<SetDistributionRateLimit>, max_per_window: u32, window_seconds: i64) -> Result<()> {
+        ctx.accounts.set_distribution_rate_limit(max_per_window, window_seconds)?;
+        Ok(())
+    }
+
+    pub fn set_protocol_pause(ctx: Context<SetProtocolPause>, paused: bool) -> Result<()> {
+        ctx.accounts.set_protocol_pause(paused)?;
+        Ok(())
+    }
+
+    pub fn get_protocol_stats(ctx: Context<GetProtocolStats>) -> Result<admin::ProtocolStats> {
+        ctx.accounts.get_protocol_stats()
+    }
+
     pub fn create_study(ctx: Context<CreateStudy>, study_id: u64, title: String, description: String, enrollment_start: i64, enrollment_end: i64, data_collection_end: i64, max_participants: u32, reward_amount: u64) -> Result<()> {
         ctx.accounts.create_study(study_id, title, description, enrollment_start, enrollment_end, data_collection_end, max_participants, reward_amount, &ctx.bumps)?;
         Ok(())
     }
 
+    pub fn update_study_settings(ctx: Context<UpdateStudySettings>, settings: study::StudySettings) -> Result<()> {
+        ctx.accounts.update_study_settings(settings)?;
+        Ok(())
+    }
+
+    pub fn update_reward_amount(ctx: Context<UpdateRewardAmount>, new_amount: u64) -> Result<()> {
+        ctx.accounts.update_reward_amount(new_amount)?;
+        Ok(())
+    }
+
+    pub fn set_study_metadata(ctx: Context<SetStudyMetadata>, extended_description: String, protocol_ipfs_cid: String) -> Result<()> {
+        ctx.accounts.set_study_metadata(extended_description, protocol_ipfs_cid, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn set_irb_approval(ctx: Context<SetIrbApproval>, approval_hash: [u8; 32], expires_at: i64) -> Result<()> {
+        ctx.accounts.set_irb_approval(approval_hash, expires_at)?;
+        Ok(())
+    }
+
+    pub fn set_notification_config(ctx: Context<SetNotificationConfig>, config_hash: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.set_notification_config(config_hash)?;
+        Ok(())
+    }
+
     pub fn publish_study(ctx: Context<PublishStudy>) -> Result<()> {
         ctx.accounts.publish_study()?;
         Ok(())
@@ -35,18 +89,97 @@ pub mod recru_search {
         Ok(())
     }
 
+    pub fn extend_study_windows(ctx: Context<ExtendStudyWindows>, new_enrollment_end: i64, new_data_collection_end: i64) -> Result<()> {
+        ctx.accounts.extend_study_windows(new_enrollment_end, new_data_collection_end)?;
+        Ok(())
+    }
+
+    pub fn archive_study(ctx: Context<ArchiveStudy>) -> Result<()> {
+        ctx.accounts.archive_study()?;
+        Ok(())
+    }
+
+    pub fn finalize_study(ctx: Context<FinalizeStudy>) -> Result<()> {
+        ctx.accounts.finalize_study()?;
+        Ok(())
+    }
+
+    pub fn get_study_timeline(ctx: Context<GetStudyTimeline>) -> Result<study::StudyTimeline> {
+        ctx.accounts.get_study_timeline()
+    }
+
+    pub fn get_remaining_slots(ctx: Context<GetRemainingSlots>) -> Result<study::RemainingSlots> {
+        ctx.accounts.get_remaining_slots()
+    }
+
+    pub fn get_study_info(ctx: Context<GetStudyInfo>) -> Result<study::StudyInfo> {
+        ctx.accounts.get_study_info()
+    }
+
+    pub fn get_study_counters(ctx: Context<GetStudyCounters>) -> Result<study::StudyCounters> {
+        ctx.accounts.get_study_counters()
+    }
+
+    pub fn get_enrollment_projection(ctx: Context<GetEnrollmentProjection>) -> Result<study::EnrollmentProjection> {
+        ctx.accounts.get_enrollment_projection()
+    }
+
     pub fn transition_study_state(ctx: Context<TransitionStudyState>) -> Result<()> {
         ctx.accounts.transition_study_state()?;
         Ok(())
     }
 
+    pub fn transition_studies_batch<'info>(ctx: Context<'_, '_, 'info, 'info, TransitionStudiesBatch<'info>>) -> Result<u32> {
+        ctx.accounts.transition_studies_batch(ctx.remaining_accounts)
+    }
+
     pub fn set_eligibility_criteria(ctx: Context<SetEligibilityCriteria>, study_id: u64, criteria: Vec<u8>) -> Result<()> {
-        ctx.accounts.set_eligibility_criteria(study_id, criteria)?;
+        ctx.accounts.set_eligibility_criteria(study_id, criteria, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn lock_eligibility_criteria(ctx: Context<LockEligibilityCriteria>) -> Result<()> {
+        ctx.accounts.lock_eligibility_criteria()?;
+        Ok(())
+    }
+
+    pub fn get_criteria_version(ctx: Context<GetCriteriaVersion>, version: u32) -> Result<eligibility_criteria::CriteriaSnapshot> {
+        ctx.accounts.get_criteria_version(version)
+    }
+
+    pub fn get_eligibility_summary(ctx: Context<GetEligibilitySummary>) -> Result<eligibility_criteria::EligibilitySummary> {
+        ctx.accounts.get_eligibility_summary()
+    }
+
+    pub fn mint_consent_nft(ctx: Context<MintConsentNFT>, study_id: u64, eligibility_proof: Vec<u8>, nullifier: Option<[u8; 32]>, invite_proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.mint_consent_nft(study_id, eligibility_proof, nullifier, invite_proof, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn get_consent_status(ctx: Context<GetConsentStatus>) -> Result<consent::ConsentStatus> {
+        ctx.accounts.get_consent_status()
+    }
+
+    pub fn check_enrollment(ctx: Context<CheckEnrollment>) -> Result<consent::EnrollmentCheck> {
+        ctx.accounts.check_enrollment()
+    }
+
+    pub fn get_participant_journey(ctx: Context<GetParticipantJourney>) -> Result<consent::ParticipantJourney> {
+        ctx.accounts.get_participant_journey()
+    }
+
+    pub fn researcher_revoke_consent(ctx: Context<ResearcherRevokeConsent>, reason_code: u8) -> Result<()> {
+        ctx.accounts.researcher_revoke_consent(reason_code)?;
+        Ok(())
+    }
+
+    pub fn opt_in_consent_expiry(ctx: Context<OptInConsentExpiry>) -> Result<()> {
+        ctx.accounts.opt_in_consent_expiry()?;
         Ok(())
     }
 
-    pub fn mint_consent_nft(ctx: Context<MintConsentNFT>, study_id: u64, eligibility_proof: Vec<u8>) -> Result<()> {
-        ctx.accounts.mint_consent_nft(study_id, eligibility_proof)?;
+    pub fn expire_consent_nft(ctx: Context<ExpireConsentNFT>) -> Result<()> {
+        ctx.accounts.expire_consent_nft()?;
         Ok(())
     }
 
@@ -55,18 +188,35 @@ pub mod recru_search {
         Ok(())
     }
 
-    pub fn submit_data(ctx: Context<SubmitData>, encrypted_data_hash: [u8; 32], ipfs_cid: String) -> Result<()> {
-        ctx.accounts.submit_data(encrypted_data_hash, ipfs_cid, &ctx.bumps)?;
+    pub fn submit_data(ctx: Context<SubmitData>, encrypted_data_hash: [u8; 32], ipfs_cid: String, submission_index: u32, encryption_scheme: EncryptionScheme) -> Result<()> {
+        ctx.accounts.submit_data(encrypted_data_hash, ipfs_cid, submission_index, encryption_scheme, &ctx.bumps)?;
         Ok(())
     }
 
+    pub fn get_submission_eligibility(ctx: Context<GetSubmissionEligibility>) -> Result<data_submission::SubmissionEligibility> {
+        ctx.accounts.get_submission_eligibility()
+    }
+
+    pub fn get_submission_status(ctx: Context<GetSubmissionStatus>) -> Result<data_submission::SubmissionStatus> {
+        ctx.accounts.get_submission_status()
+    }
+
+    pub fn verify_data_quality(ctx: Context<VerifyDataQuality>, response_id: u32, quality_score: u8) -> Result<()> {
+        ctx.accounts.verify_data_quality(response_id, quality_score, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn verify_submissions_batch<'info>(ctx: Context<'_, '_, 'info, 'info, VerifySubmissionsBatch<'info>>) -> Result<u32> {
+        ctx.accounts.verify_submissions_batch(ctx.remaining_accounts)
+    }
+
     pub fn mint_completion_nft(ctx: Context<MintCompletionNFT>) -> Result<()> {
         ctx.accounts.mint_completion_nft()?;
         Ok(())
     }
 
-    pub fn create_reward_vault(ctx: Context<CreateRewardVault>, study_id: u64, initial_deposit: u64) -> Result<()> {
-        ctx.accounts.create_reward_vault(study_id, initial_deposit, &ctx.bumps)?;
+    pub fn create_reward_vault(ctx: Context<CreateRewardVault>, study_id: u64, initial_deposit: u64, allow_overfund: bool) -> Result<()> {
+        ctx.accounts.create_reward_vault(study_id, initial_deposit, allow_overfund, &ctx.bumps)?;
         Ok(())
     }
 
@@ -75,8 +225,95 @@ pub mod recru_search {
         Ok(())
     }
 
-    pub fn create_survey_schema(ctx: Context<CreateSurveySchema>, study_id: u64, survey_title: String, schema_ipfs_cid: String, requires_encryption: bool) -> Result<()> {
-        ctx.accounts.create_survey_schema(study_id, survey_title, schema_ipfs_cid, requires_encryption, &ctx.bumps)?;
+    pub fn distribute_variable_reward(ctx: Context<DistributeReward>, amount: u64) -> Result<()> {
+        ctx.accounts.distribute_variable_reward(amount, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn update_reward_recipient(ctx: Context<UpdateRewardRecipient>, new_recipient: Pubkey) -> Result<()> {
+        ctx.accounts.update_reward_recipient(new_recipient)?;
+        Ok(())
+    }
+
+    pub fn simulate_distribution(ctx: Context<SimulateDistribution>) -> Result<rewards::DistributionSimulation> {
+        ctx.accounts.simulate_distribution()
+    }
+
+    pub fn release_completion_bonus<'info>(ctx: Context<'_, '_, 'info, 'info, ReleaseCompletionBonus<'info>>) -> Result<u32> {
+        ctx.accounts.release_completion_bonus(ctx.remaining_accounts)
+    }
+
+    pub fn initialize_distribution_ledger(ctx: Context<InitializeDistributionLedger>) -> Result<()> {
+        ctx.accounts.initialize_distribution_ledger(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn get_distribution_ledger_page(ctx: Context<GetDistributionLedgerPage>, offset: u32, limit: u32) -> Result<distribution_ledger::LedgerPage> {
+        ctx.accounts.get_distribution_ledger_page(offset, limit)
+    }
+
+    pub fn reclaim_revoked_slot(ctx: Context<ReclaimRevokedSlot>) -> Result<()> {
+        ctx.accounts.reclaim_revoked_slot()?;
+        Ok(())
+    }
+
+    pub fn top_up_reward_vault(ctx: Context<TopUpRewardVault>, amount: u64, allow_overfund: bool) -> Result<()> {
+        ctx.accounts.top_up_reward_vault(amount, allow_overfund)?;
+        Ok(())
+    }
+
+    pub fn withdraw_excess_funding(ctx: Context<WithdrawExcessFunding>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw_excess_funding(amount)?;
+        Ok(())
+    }
+
+    pub fn withdraw_remaining_rewards(ctx: Context<WithdrawRemainingRewards>) -> Result<()> {
+        ctx.accounts.withdraw_remaining_rewards()?;
+        Ok(())
+    }
+
+    pub fn finalize_expired_study(ctx: Context<FinalizeExpiredStudy>) -> Result<()> {
+        ctx.accounts.finalize_expired_study()?;
+        Ok(())
+    }
+
+    pub fn recover_stray_tokens(ctx: Context<RecoverStrayTokens>) -> Result<()> {
+        ctx.accounts.recover_stray_tokens()?;
+        Ok(())
+    }
+
+    pub fn set_reward_merkle_root(ctx: Context<SetRewardMerkleRoot>, merkle_root: [u8; 32]) -> Result<()> {
+        ctx.accounts.set_reward_merkle_root(merkle_root)?;
+        Ok(())
+    }
+
+    pub fn set_invite_merkle_root(ctx: Context<SetInviteMerkleRoot>, invite_merkle_root: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.set_invite_merkle_root(invite_merkle_root)?;
+        Ok(())
+    }
+
+    pub fn claim_merkle_reward(ctx: Context<ClaimMerkleReward>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        ctx.accounts.claim_merkle_reward(amount, proof, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn pause_rewards(ctx: Context<SetRewardPause>) -> Result<()> {
+        ctx.accounts.set_reward_pause(true)?;
+        Ok(())
+    }
+
+    pub fn resume_rewards(ctx: Context<SetRewardPause>) -> Result<()> {
+        ctx.accounts.set_reward_pause(false)?;
+        Ok(())
+    }
+
+    pub fn submit_study_rating(ctx: Context<SubmitStudyRating>, score: u8, comment_hash: Option<[u8; 32]>) -> Result<()> {
+        ctx.accounts.submit_study_rating(score, comment_hash, &ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn create_survey_schema(ctx: Context<CreateSurveySchema>, study_id: u64, survey_title: String, schema_ipfs_cid: String, requires_encryption: bool, estimated_duration_minutes: u16) -> Result<()> {
+        ctx.accounts.create_survey_schema(study_id, survey_title, schema_ipfs_cid, requires_encryption, estimated_duration_minutes, &ctx.bumps)?;
         Ok(())
     }
 
@@ -85,7 +322,31 @@ pub mod recru_search {
         Ok(())
     }
 
-    pub fn export_survey_data(ctx: Context<ExportSurveyData>, study_id: u64) -> Result<data_management::ExportManifest> {
-        ctx.accounts.export_survey_data(study_id)
+    pub fn set_allowed_cid_prefixes(ctx: Context<SetAllowedCidPrefixes>, prefixes: Vec<String>) -> Result<()> {
+        ctx.accounts.set_allowed_cid_prefixes(prefixes)?;
+        Ok(())
+    }
+
+    pub fn record_file_upload(ctx: Context<RecordFileUpload>, file_size_bytes: u64) -> Result<()> {
+        ctx.accounts.record_file_upload(file_size_bytes)?;
+        Ok(())
+    }
+
+    pub fn anonymize_participant_data(ctx: Context<AnonymizeParticipantData>) -> Result<()> {
+        ctx.accounts.anonymize_participant_data()?;
+        Ok(())
+    }
+
+    pub fn record_final_stats(ctx: Context<RecordFinalStats>) -> Result<()> {
+        ctx.accounts.record_final_stats(&ctx.bumps)?;
+        Ok(())
+    }
+
+    pub fn get_final_stats(ctx: Context<GetFinalStats>) -> Result<data_management::FinalStatsSummary> {
+        ctx.accounts.get_final_stats()
+    }
+
+    pub fn export_survey_data(ctx: Context<ExportSurveyData>, study_id: u64, anonymization: data_management::AnonymizationConfig) -> Result<data_management::ExportManifest> {
+        ctx.accounts.export_survey_data(study_id, anonymization)
     }
 }
\ No newline at end of file
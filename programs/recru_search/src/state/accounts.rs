@@ -1,4 +1,8 @@
 use anchor_lang::prelude::*;
+use crate::state::constants::{
+    CONSENT_STATE_ACTIVE, CONSENT_STATE_EXPIRED, CONSENT_STATE_REVOKED, MAX_LEDGER_ENTRIES, RETENTION_GRACE_PERIOD,
+    WITHDRAWAL_GRACE_PERIOD,
+};
 
 // Study status enum 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace, Debug)]
@@ -7,6 +11,51 @@ pub enum StudyStatus {
     Published,
     Active,
     Closed,
+    Archived,
+}
+
+// How participants are rewarded for completing a study
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum RewardType {
+    TokenOnly,
+    NftOnly,
+    TokenAndNft,
+}
+
+// Who funds the recipient ATA when a payout has to create it.
+// Participant keeps researcher costs flat but needs the participant to sign with enough SOL;
+// Researcher lets payouts be pushed to wallets that hold no SOL at the researcher's expense.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum AtaRentPayer {
+    Participant,
+    Researcher,
+}
+
+// What a participant must reach before their reward can be paid
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum CompletionRequirement {
+    OnSubmission,
+    OnVerification,
+    OnCompletionNFT,
+}
+
+// Encryption applied to a submission's payload, recorded for auditors
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum EncryptionScheme {
+    None,
+    AesGcm,
+    NaclBox,
+    XChaCha20Poly1305,
+}
+
+impl EncryptionScheme {
+    // Every scheme allowed - the default for new studies
+    pub const ALL: u8 = 0b1111;
+
+    // Bit for this scheme in a study's allowed-scheme mask
+    pub fn mask(self) -> u8 {
+        1 << self as u8
+    }
 }
 
 // Global admin account 
@@ -19,10 +68,35 @@ pub struct AdminAccount {
     pub max_study_duration: u64,
     pub total_studies: u64,
     pub total_participants: u64,
+    pub unique_participants: u64,
     pub total_rewards_distributed: u64,
+    pub total_active_liability: u64,
+    #[max_len(100)]
+    pub preferred_ipfs_gateway: String,
+    pub extension_requires_admin_above_seconds: i64,
+    pub withdrawal_grace_period_override: Option<i64>,
+    pub retention_grace_period_override: Option<i64>,
+    // Per-researcher cap on distributions within a rolling window; zero disables it
+    pub max_distributions_per_window: u32,
+    pub distribution_window_seconds: i64,
+    // Set once by initialize_protocol; there is no path that clears it
+    pub is_initialized: bool,
+    // Circuit breaker - blocks study creation, enrollment, submissions and payouts
+    pub is_paused: bool,
     pub bump: u8,
 }
 
+impl AdminAccount {
+    // Effective grace windows - the admin override wins over the protocol default
+    pub fn withdrawal_grace_period(&self) -> i64 {
+        self.withdrawal_grace_period_override.unwrap_or(WITHDRAWAL_GRACE_PERIOD)
+    }
+
+    pub fn retention_grace_period(&self) -> i64 {
+        self.retention_grace_period_override.unwrap_or(RETENTION_GRACE_PERIOD)
+    }
+}
+
 // Study account 
 #[account]
 #[derive(InitSpace)]
@@ -41,15 +115,85 @@ pub struct StudyAccount {
     pub reward_amount_per_participant: u64,
     pub status: StudyStatus,
     pub completed_count: u32,
+    pub submitted_count: u32,
+    pub total_revocations: u32,
     pub total_rewards_distributed: u64,
     pub created_at: i64,
+    pub published_at: Option<i64>,
+    pub closed_at: Option<i64>,
+    pub archived_at: Option<i64>,
 
     pub has_eligibility_criteria: bool,
     #[max_len(500)]
     pub eligibility_criteria: Vec<u8>,
+    pub criteria_version: u32,
+    pub criteria_locked: bool,
+    pub reward_merkle_root: Option<[u8; 32]>,
+    // Root of the invited-wallet tree; when set, enrollment needs an inclusion proof on top of eligibility
+    pub invite_merkle_root: Option<[u8; 32]>,
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    pub completion_bonus_pool: u64,
+    pub completion_target_bps: u16,
+    pub completion_bonus_per_participant: u64,
+    pub completion_bonus_released: bool,
+    // Running total of bonus payouts, capped at completion_bonus_pool
+    pub completion_bonus_paid_total: u64,
+    pub submissions_per_participant: u32,
+    pub completion_nft_cap: Option<u32>,
+    pub pool_split_mode: bool,
+    pub reward_pool: u64,
+    pub pool_distributed: u64,
+    pub pool_paid_count: u32,
+    // Submissions marked reward_distributed by a vault payout, across every reward mode
+    pub rewarded_count: u32,
+    pub has_survey_schema: bool,
+    pub survey_finalized: bool,
+    pub requires_irb_approval: bool,
+    pub irb_approval_hash: [u8; 32],
+    pub irb_approval_expires: i64,
+    pub max_variable_reward: u64,
+    pub detect_duplicate_hashes: bool,
+    pub completion_requirement: CompletionRequirement,
+    pub requires_verification: bool,
+    pub preserve_revoked_nft: bool,
+    // Set once the study is wrapped up; every study-mutating instruction rejects afterwards
+    pub is_finalized: bool,
+    // Bitmask of EncryptionScheme values submissions may use
+    pub allowed_encryption_schemes: u8,
+    pub encrypted_responses: u32,
+    // Hash of the researcher's off-chain notification config, echoed in events for relayers
+    pub notification_config_hash: Option<[u8; 32]>,
+    pub last_known_vault_balance: u64,
+    pub vault_balance_updated_at: i64,
+    pub ata_rent_payer: AtaRentPayer,
+    pub is_reward_paused: bool,
+    pub allow_researcher_participation: bool,
+    pub reward_type: RewardType,
+    pub reward_vault: Option<Pubkey>,
+    pub age_oracle: Option<Pubkey>,
+    pub requires_unique_identity: bool,
     pub bump: u8,
 }
 
+impl StudyAccount {
+    // Completion certificates available - defaults to one per participant slot
+    pub fn completion_nft_limit(&self) -> u32 {
+        self.completion_nft_cap.unwrap_or(self.max_participants)
+    }
+
+    // Snapshot of the vault token balance so reads don't need the token account
+    pub fn cache_vault_balance(&mut self, balance: u64, now: i64) {
+        self.last_known_vault_balance = balance;
+        self.vault_balance_updated_at = now;
+    }
+
+    // A recorded approval counts until its expiry timestamp
+    pub fn has_valid_irb_approval(&self, now: i64) -> bool {
+        self.irb_approval_hash != [0u8; 32] && now < self.irb_approval_expires
+    }
+}
+
 // Consent account 
 #[account]
 #[derive(InitSpace)]
@@ -62,6 +206,140 @@ pub struct ConsentAccount {
     pub is_revoked: bool,
     pub revocation_timestamp: Option<i64>,
     pub nft_mint: Option<Pubkey>,
+    pub consent_valid_until: i64,
+    pub expiry_opt_in: bool,
+    pub is_expired: bool,
+    pub eligibility_verified: bool,
+    pub eligibility_method: u8,
+    pub revoked_by_researcher: bool,
+    pub revocation_reason: u8,
+    pub submission_count: u32,
+    pub reward_recipient: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl ConsentAccount {
+    // Lifecycle state for the audit stream - revocation wins over expiry
+    pub fn state(&self) -> u8 {
+        if self.is_revoked {
+            CONSENT_STATE_REVOKED
+        } else if self.is_expired {
+            CONSENT_STATE_EXPIRED
+        } else {
+            CONSENT_STATE_ACTIVE
+        }
+    }
+}
+
+// Identity nullifier - one enrollment per off-chain identity per study
+#[account]
+#[derive(InitSpace)]
+pub struct IdentityNullifier {
+    pub study: Pubkey,
+    pub nullifier: [u8; 32],
+    pub participant: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// Global participant marker - created on a wallet's first enrollment in any study
+#[account]
+#[derive(InitSpace)]
+pub struct GlobalParticipant {
+    pub participant: Pubkey,
+    pub first_study: Pubkey,
+    pub first_enrolled_at: i64,
+    pub bump: u8,
+}
+
+// Researcher profile - distribution rate tracking and rating totals, created on first payout or rating
+#[account]
+#[derive(InitSpace)]
+pub struct ResearcherProfile {
+    pub researcher: Pubkey,
+    pub distribution_window_start: i64,
+    pub distributions_in_window: u32,
+    // Running rating totals across all of the researcher's studies
+    pub rating_sum: u64,
+    pub rating_count: u32,
+    pub bump: u8,
+}
+
+impl ResearcherProfile {
+    // Counts one distribution, starting a fresh window once the current one has elapsed;
+    // false when the window is already at the cap
+    pub fn record_distribution(&mut self, now: i64, max_per_window: u32, window_seconds: i64) -> bool {
+        if now >= self.distribution_window_start.saturating_add(window_seconds) {
+            self.distribution_window_start = now;
+            self.distributions_in_window = 0;
+        }
+
+        if max_per_window > 0 && self.distributions_in_window >= max_per_window {
+            return false;
+        }
+        self.distributions_in_window += 1;
+
+        true
+    }
+}
+
+// Study metadata - long-form description and protocol documents, kept off the study for cheap listing
+#[account]
+#[derive(InitSpace)]
+pub struct StudyMetadata {
+    pub study: Pubkey,
+    #[max_len(2000)]
+    pub extended_description: String,
+    #[max_len(100)]
+    pub protocol_ipfs_cid: String,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+// Data hash record - first submission of each encrypted data hash in a study
+#[account]
+#[derive(InitSpace)]
+pub struct DataHashRecord {
+    pub study: Pubkey,
+    pub data_hash: [u8; 32],
+    pub participant: Pubkey,
+    pub submission: Pubkey,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+// Merkle reward claim - marks a participant's airdrop as paid
+#[account]
+#[derive(InitSpace)]
+pub struct MerkleClaim {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub claimed_at: i64,
+    pub bump: u8,
+}
+
+// Study rating - one per completed participant
+#[account]
+#[derive(InitSpace)]
+pub struct StudyRating {
+    pub study: Pubkey,
+    pub participant: Pubkey,
+    pub score: u8,
+    pub comment_hash: Option<[u8; 32]>,
+    pub rated_at: i64,
+    pub bump: u8,
+}
+
+// Eligibility criteria version - append-only history of criteria changes
+#[account]
+#[derive(InitSpace)]
+pub struct CriteriaVersion {
+    pub study: Pubkey,
+    pub version: u32,
+    #[max_len(500)]
+    pub criteria: Vec<u8>,
+    pub created_at: i64,
     pub bump: u8,
 }
 
@@ -71,6 +349,7 @@ pub struct ConsentAccount {
 pub struct SubmissionAccount {
     pub study: Pubkey,
     pub participant: Pubkey,
+    pub index: u32,
     pub encrypted_data_hash: [u8; 32],
     #[max_len(100)]
     pub ipfs_cid: String,
@@ -78,6 +357,22 @@ pub struct SubmissionAccount {
     pub is_verified: bool,
     pub reward_distributed: bool,
     pub completion_nft_mint: Option<Pubkey>,
+    pub completion_timestamp: i64,
+    pub bonus_paid: bool,
+    pub reward_amount: u64,
+    pub encryption_scheme: EncryptionScheme,
+    pub bump: u8,
+}
+
+// Response record - per-response validation provenance
+#[account]
+#[derive(InitSpace)]
+pub struct ResponseRecord {
+    pub submission: Pubkey,
+    pub response_id: u32,
+    pub quality_score: u8,
+    pub validated_at: i64,
+    pub validator: Pubkey,
     pub bump: u8,
 }
 
@@ -87,9 +382,44 @@ pub struct SubmissionAccount {
 pub struct RewardVault {
     pub study: Pubkey,
     pub reward_token_mint: Pubkey,
+    pub reward_mint_decimals: u8,
     pub total_deposited: u64,
     pub total_distributed: u64,
+    pub reclaimable_slots: u32,
+    // This vault's share of the protocol-wide active liability still outstanding
+    pub reserved_liability: u64,
+    pub bump: u8,
+}
+
+// Distribution ledger - ordered payout record, zero-copy to fit a fixed entry array
+#[account(zero_copy)]
+pub struct DistributionLedger {
+    pub study: Pubkey,
+    pub entry_count: u32,
     pub bump: u8,
+    pub _padding: [u8; 3],
+    pub entries: [LedgerEntry; MAX_LEDGER_ENTRIES],
+}
+
+#[zero_copy]
+pub struct LedgerEntry {
+    pub participant: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+impl DistributionLedger {
+    // Appends a payout; returns false once the ledger is full
+    pub fn append(&mut self, participant: Pubkey, amount: u64, timestamp: i64) -> bool {
+        let index = self.entry_count as usize;
+        if index >= MAX_LEDGER_ENTRIES {
+            return false;
+        }
+
+        self.entries[index] = LedgerEntry { participant, amount, timestamp };
+        self.entry_count += 1;
+        true
+    }
 }
 
 // Survey schema account 
@@ -102,6 +432,10 @@ pub struct SurveySchema {
     #[max_len(100)]
     pub schema_ipfs_cid: String,
     pub requires_encryption: bool,
+    pub estimated_duration_minutes: u16,
+    // Accepted response CID prefixes (e.g. a pinning service's namespace); empty accepts any CID
+    #[max_len(5, 20)]
+    pub allowed_cid_prefixes: Vec<String>,
     pub bump: u8,
 }
 
@@ -113,5 +447,29 @@ pub struct DataCollectionStats {
     pub researcher: Pubkey,
     pub total_responses: u32,
     pub complete_responses: u32,
+    // Responses with a ResponseRecord from verify_data_quality
+    pub validated_responses: u32,
+    // Submissions flagged is_verified by verify_submissions_batch
+    pub verified_submissions: u32,
+    pub anonymized_responses: u32,
+    pub total_files: u32,
+    pub total_file_size_bytes: u64,
+    pub bump: u8,
+}
+
+// Final statistics - write-once snapshot of data collection taken after the study closes
+#[account]
+#[derive(InitSpace)]
+pub struct StudyFinalStats {
+    pub study: Pubkey,
+    pub total_responses: u32,
+    pub complete_responses: u32,
+    pub validated_responses: u32,
+    pub total_files_uploaded: u32,
+    pub total_file_size_bytes: u64,
+    pub enrolled_count: u32,
+    pub completed_count: u32,
+    pub closed_at: i64,
+    pub recorded_at: i64,
     pub bump: u8,
 }
\ No newline at end of file
@@ -6,6 +6,8 @@ pub const SEED: &str = "anchor";
 // Content length limits for study metadata
 pub const MAX_TITLE_LENGTH: usize = 100;
 pub const MAX_DESCRIPTION_LENGTH: usize = 500;
+pub const MAX_EXTENDED_DESCRIPTION_LENGTH: usize = 2000;
+pub const MAX_GATEWAY_LENGTH: usize = 100;
 
 // Study duration constraints 
 #[constant]
@@ -14,22 +16,95 @@ pub const MIN_STUDY_DURATION: i64 = 86400; // 1 day
 pub const MAX_STUDY_DURATION: i64 = 31536000; // 1 year
 #[constant]
 pub const MIN_ENROLLMENT_WINDOW: i64 = 3600; // 1 hour
+#[constant]
+pub const MIN_DATA_COLLECTION_WINDOW: i64 = 3600; // 1 hour
+#[constant]
+pub const MAX_ENROLLMENT_LEAD_TIME: i64 = 31536000; // 1 year
+pub const SECONDS_PER_DAY: i64 = 86400;
+#[constant]
+pub const DEFAULT_EXTENSION_APPROVAL_THRESHOLD: i64 = 604800; // 7 days
+#[constant]
+pub const WITHDRAWAL_GRACE_PERIOD: i64 = 604800; // 7 days
+#[constant]
+pub const RETENTION_GRACE_PERIOD: i64 = 2592000; // 30 days
+#[constant]
+pub const REWARD_CLAIM_DELAY: i64 = 86400; // 24 hours after the final submission
+
+// Survey duration limits (minutes)
+pub const MIN_SURVEY_DURATION_MINUTES: u16 = 1;
+pub const MAX_SURVEY_DURATION_MINUTES: u16 = 1440; // 1 day
+
+// Response CID prefix allow-list limits (must match SurveySchema max_len)
+pub const MAX_CID_PREFIXES: usize = 5;
+pub const MAX_CID_PREFIX_LENGTH: usize = 20;
+
+// Smallest group size that counts as k-anonymous
+pub const MIN_K_ANONYMITY_THRESHOLD: u8 = 2;
+
+// Study rating bounds
+pub const MIN_STUDY_RATING: u8 = 1;
+pub const MAX_STUDY_RATING: u8 = 5;
+
+// Response quality score upper bound
+pub const MAX_QUALITY_SCORE: u8 = 100;
 
 // Study participation limits
 pub const MAX_PARTICIPANTS_PER_STUDY: u32 = 10000;
 
+// Repeated-measures cap (e.g. a year of daily diaries)
+pub const MAX_SUBMISSIONS_PER_PARTICIPANT: u32 = 365;
+
+// Max accounts processed by a single batch instruction
+pub const MAX_BATCH_SIZE: usize = 10;
+
+// Distribution ledger capacity, and entries per read (bounded by the 1KB return data limit)
+pub const MAX_LEDGER_ENTRIES: usize = 200;
+pub const MAX_LEDGER_PAGE_SIZE: u32 = 20;
+
+// Deepest merkle proof accepted for reward claims (~1M recipients)
+pub const MAX_MERKLE_PROOF_LENGTH: usize = 20;
+
 // Protocol fee constants
 pub const DEFAULT_PROTOCOL_FEE_BPS: u16 = 250; // 2.5%
 pub const MAX_PROTOCOL_FEE_BPS: u16 = 1000; // 10%
 
-// NFT symbols
+// Vault funding cap relative to max reward liability
+pub const MAX_VAULT_FUNDING_BPS: u64 = 11000; // 110%
+
+// Keeper incentive for sweeping an expired study's vault
+pub const KEEPER_FEE_BPS: u64 = 50; // 0.5%
+
+// Base SPL token account size, used to price recipient ATA rent before creating it
+pub const TOKEN_ACCOUNT_SIZE: usize = 165;
+
+// NFT symbols, written to each minted asset's "Symbol" attribute
+#[constant]
 pub const CONSENT_NFT_SYMBOL: &str = "RCONSENT";
+#[constant]
 pub const COMPLETION_NFT_SYMBOL: &str = "RCOMPLETE";
 
 // Template images for NFTs (standard images with dynamic metadata)
 pub const CONSENT_NFT_TEMPLATE_IMAGE: &str = "ipfs://bafkreiaich32x7g4cajovenhlnvn3jfedf3vkh4pqiyfa6g2e26zi7chkm";
 pub const COMPLETION_NFT_TEMPLATE_IMAGE: &str = "ipfs://bafkreiaich32x7g4cajovenhlnvn3jfedf3vkh4pqiyfa6g2e26zi7chkm";
 
+// How a participant's eligibility was established at enrollment
+pub const ELIGIBILITY_METHOD_NONE: u8 = 0;
+pub const ELIGIBILITY_METHOD_CRITERIA: u8 = 1;
+pub const ELIGIBILITY_METHOD_ORACLE: u8 = 2;
+
+// Consent lifecycle states reported by ConsentStateChanged
+pub const CONSENT_STATE_NONE: u8 = 0;
+pub const CONSENT_STATE_ACTIVE: u8 = 1;
+pub const CONSENT_STATE_REVOKED: u8 = 2;
+pub const CONSENT_STATE_EXPIRED: u8 = 3;
+
+// Why a participant can't submit data right now
+pub const SUBMISSION_BLOCKED_STUDY_STATE: u8 = 0;
+pub const SUBMISSION_BLOCKED_DEADLINE_PASSED: u8 = 1;
+pub const SUBMISSION_BLOCKED_NOT_ENROLLED: u8 = 2;
+pub const SUBMISSION_BLOCKED_CONSENT_INACTIVE: u8 = 3;
+pub const SUBMISSION_BLOCKED_ALREADY_SUBMITTED: u8 = 4;
+
 // Basic eligibility constraints
 pub const MIN_AGE_LIMIT: u8 = 18;
 pub const MAX_AGE_LIMIT: u8 = 100;
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::events::StudyError;
 
 #[error_code]
 pub enum RecruSearchError {
@@ -21,6 +22,18 @@ pub enum RecruSearchError {
     InvalidMaxParticipants = 6007,
     #[msg("Invalid parameter value provided")]
     InvalidParameterValue = 6008,
+    #[msg("Batch exceeds the maximum number of accounts")]
+    BatchTooLarge = 6009,
+    #[msg("Data collection must continue at least 1 hour after enrollment ends")]
+    DataCollectionWindowTooShort = 6010,
+    #[msg("K-anonymity requires a k threshold of at least 2")]
+    InvalidKThreshold = 6011,
+    #[msg("Rating score must be between 1 and 5")]
+    InvalidRatingScore = 6012,
+    #[msg("Enrollment start is more than a year in the future")]
+    EnrollmentStartTooFar = 6013,
+    #[msg("Extended description exceeds maximum length of 2,000 characters")]
+    ExtendedDescriptionTooLong = 6014,
 
     // Access control errors 
     #[msg("Only the study researcher can perform this action")]
@@ -29,6 +42,14 @@ pub enum RecruSearchError {
     UnauthorizedParticipant = 6101,
     #[msg("Insufficient permissions to perform this action")]
     UnauthorizedAccess = 6102,
+    #[msg("Researcher cannot receive rewards from their own study")]
+    SelfDealingNotAllowed = 6103,
+    #[msg("Participant address is invalid")]
+    InvalidParticipant = 6104,
+    #[msg("Extension exceeds the researcher limit and needs protocol admin approval")]
+    AdminApprovalRequired = 6105,
+    #[msg("Protocol is paused by the admin")]
+    ProtocolPaused = 6106,
 
     // State transition errors 
     #[msg("Study is not in the required state for this operation")]
@@ -43,6 +64,30 @@ pub enum RecruSearchError {
     StudyAlreadyClosed = 6204,
     #[msg("Consent is not active or has been revoked")]
     ConsentNotActive = 6205,
+    #[msg("Study has been archived and no longer accepts changes")]
+    StudyArchived = 6206,
+    #[msg("Eligibility criteria are locked and can no longer change")]
+    EligibilityLocked = 6207,
+    #[msg("Asset account is already initialized")]
+    AssetAlreadyInitialized = 6208,
+    #[msg("Study has issued all of its completion NFTs")]
+    CompletionCapReached = 6209,
+    #[msg("Survey schema must be finalized before data can be submitted")]
+    SurveyNotFinalized = 6210,
+    #[msg("Study requires an IRB approval before publishing")]
+    IrbApprovalMissing = 6211,
+    #[msg("Study IRB approval has expired")]
+    IrbApprovalExpired = 6212,
+    #[msg("Study has been finalized and can no longer change")]
+    StudyFinalized = 6213,
+    #[msg("Every enrolled participant must complete before the study is finalized")]
+    StudyHasIncompleteParticipants = 6214,
+    #[msg("Enrollment has not ended yet")]
+    EnrollmentStillOpen = 6215,
+    #[msg("Protocol has already been initialized")]
+    ProtocolAlreadyInitialized = 6216,
+    #[msg("Reward claim deadline has not passed yet")]
+    ClaimDeadlineNotReached = 6217,
 
     // Data validation errors
     #[msg("Data format is invalid or corrupted")]
@@ -55,12 +100,44 @@ pub enum RecruSearchError {
     ParticipantNotEligible = 6303,
     #[msg("Study has no eligibility criteria set for verification")]
     NoEligibilityCriteria = 6304,
+    #[msg("Identical data has already been submitted to this study")]
+    DuplicateSubmissionData = 6305,
+    #[msg("Encryption scheme is not allowed by this study")]
+    EncryptionSchemeNotAllowed = 6306,
+    #[msg("IPFS CID does not match any prefix allowed by the survey schema")]
+    CidNotAllowed = 6307,
+    #[msg("Participant is not on the study's invite list")]
+    NotOnInviteList = 6308,
 
     // Participant action errors 
     #[msg("Consent has been revoked and cannot be used")]
     ConsentRevoked = 6400,
     #[msg("Data has already been submitted for this study")]
     AlreadySubmitted = 6401,
+    #[msg("Consent has expired and the NFT has been burned")]
+    ConsentExpired = 6402,
+    #[msg("Participant has not opted in to consent NFT expiry")]
+    ExpiryNotOptedIn = 6403,
+    #[msg("Consent is still within its validity period")]
+    ConsentStillValid = 6404,
+    #[msg("Participant age has not been verified by the study's age oracle")]
+    AgeNotVerified = 6405,
+    #[msg("Study requires a unique identity nullifier to enroll")]
+    IdentityRequired = 6406,
+    #[msg("Identity nullifier has already been used to enroll in this study")]
+    NullifierAlreadyUsed = 6407,
+    #[msg("Participant has not completed the study")]
+    StudyNotCompleted = 6408,
+    #[msg("Participant has already rated this study")]
+    AlreadyRated = 6409,
+    #[msg("Participant has reached the study's submission limit")]
+    SubmissionLimitReached = 6410,
+    #[msg("Participant has not completed every required submission")]
+    SubmissionSeriesIncomplete = 6411,
+    #[msg("Submission must be verified before the reward is paid")]
+    SubmissionNotVerified = 6412,
+    #[msg("Completion NFT must be minted before the reward is paid")]
+    CompletionNftRequired = 6413,
 
     // Token and reward errors
     #[msg("Insufficient token balance for this operation")]
@@ -73,10 +150,64 @@ pub enum RecruSearchError {
     RewardNotDistributed = 6503,
     #[msg("Protocol fee exceeds maximum allowed rate of 10%")]
     ExcessiveProtocolFee = 6504,
+    #[msg("Vault funding exceeds 110% of the study's maximum reward liability")]
+    ExcessiveVaultFunding = 6505,
+    #[msg("Reward distribution is paused for this study")]
+    RewardsPaused = 6506,
+    #[msg("Operation is not supported by the study's reward type")]
+    InvalidRewardType = 6507,
+    #[msg("Reward vault already exists for this study")]
+    VaultAlreadyExists = 6508,
+    #[msg("No revoked participant slots are available to reclaim")]
+    NoReclaimableSlots = 6509,
+    #[msg("Participant token account has the wrong mint or owner")]
+    TokenAccountMismatch = 6510,
+    #[msg("Study has no reward merkle root")]
+    MerkleRootNotSet = 6511,
+    #[msg("Merkle proof does not match the study's reward root")]
+    InvalidMerkleProof = 6512,
+    #[msg("Study has not reached its completion-rate target")]
+    CompletionTargetNotMet = 6513,
+    #[msg("Reward vault must exist and fund at least one participant")]
+    VaultNotFunded = 6514,
+    #[msg("Reward amount must be greater than zero")]
+    InvalidRewardAmount = 6515,
+    #[msg("Reward cannot be claimed until 24 hours after submission")]
+    ClaimTooEarly = 6516,
+    #[msg("Pool rewards can only be distributed after the study closes")]
+    PoolNotFinalized = 6517,
+    #[msg("Variable reward exceeds the study's ceiling")]
+    VariableRewardAboveCeiling = 6518,
+    #[msg("Reward mint balances can't be recovered as stray tokens")]
+    RewardMintNotRecoverable = 6519,
+    #[msg("ATA rent payer lacks the SOL to create the recipient token account")]
+    InsufficientRentBalance = 6520,
+    #[msg("Researcher has reached the protocol's distribution rate limit for this window")]
+    DistributionRateExceeded = 6521,
+    #[msg("Withdrawal would leave the vault short of what enrolled participants are owed")]
+    WithdrawalExceedsExcess = 6522,
+    #[msg("Submitted participants are still waiting on their rewards")]
+    UnpaidSubmissionsRemain = 6523,
+    #[msg("Reward vault must be withdrawn or emptied before the study is finalized")]
+    RewardVaultNotEmpty = 6524,
 
     // Processing errors 
     #[msg("Data anonymization process failed")]
     AnonymizationFailed = 6600,
     #[msg("Arithmetic overflow or underflow occurred")]
     ArithmeticError = 6601,
-}
\ No newline at end of file
+    #[msg("Study participant counts are inconsistent")]
+    CountInvariantViolated = 6602,
+}
+
+// Emits StudyError so failures show up in on-chain analytics, then hands the error back to return
+pub fn study_error(study_id: u64, error: RecruSearchError) -> Error {
+    emit!(StudyError {
+        study_id,
+        error_code: error.into(),
+        error_message: error.to_string(),
+        timestamp: Clock::get().map(|clock| clock.unix_timestamp).unwrap_or_default(),
+    });
+
+    error.into()
+}
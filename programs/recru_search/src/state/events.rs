@@ -23,6 +23,44 @@ pub struct StudyCreated {
 pub struct StudyPublished {
     pub study_id: u64,
     pub researcher: Pubkey,
+    pub notification_config_hash: Option<[u8; 32]>,
+}
+
+#[event]
+pub struct StudyMetadataUpdated {
+    pub study_id: u64,
+    pub protocol_ipfs_cid: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct NotificationConfigUpdated {
+    pub study_id: u64,
+    pub notification_config_hash: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct InviteMerkleRootSet {
+    pub study_id: u64,
+    pub invite_merkle_root: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IrbApprovalSet {
+    pub study_id: u64,
+    pub approval_hash: [u8; 32],
+    pub expires_at: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardAmountUpdated {
+    pub study_id: u64,
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub timestamp: i64,
 }
 
 #[event]
@@ -31,6 +69,65 @@ pub struct StudyClosed {
     pub researcher: Pubkey,
     pub total_participants: u32,
     pub total_submissions: u32,
+    pub total_revocations: u32,
+    pub completed_count: u32,
+    pub notification_config_hash: Option<[u8; 32]>,
+}
+
+#[event]
+pub struct StudyWindowsExtended {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub enrollment_end: i64,
+    pub data_collection_end: i64,
+    pub admin_approved: bool,
+}
+
+#[event]
+pub struct ExtensionApprovalRequired {
+    pub study_id: u64,
+    pub requested_extension: i64,
+    pub threshold: i64,
+    pub protocol_admin: Pubkey,
+}
+
+#[event]
+pub struct StudyFinalized {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub completed_count: u32,
+    pub total_revocations: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StudyArchived {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EligibilityCriteriaVersioned {
+    pub study_id: u64,
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EligibilityCriteriaLocked {
+    pub study_id: u64,
+    pub version: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StudyRated {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub score: u8,
+    pub rating_count: u32,
+    pub timestamp: i64,
 }
 
 // track participant enrollment and withdrawal
@@ -49,6 +146,23 @@ pub struct ConsentRevoked {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct ConsentRevokedByResearcher {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub researcher: Pubkey,
+    pub reason_code: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ConsentExpired {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub consent_nft_mint: Pubkey,
+    pub timestamp: i64,
+}
+
 //  track encrypted data uploads
 #[event]
 pub struct DataSubmitted {
@@ -58,6 +172,40 @@ pub struct DataSubmitted {
     pub timestamp: i64,
 }
 
+#[event]
+pub struct SubmissionsVerified {
+    pub study_id: u64,
+    pub verified_count: u32,
+    pub timestamp: i64,
+}
+
+// Same as DataSubmitted, plus the protocol's preferred gateway for direct links
+#[event]
+pub struct DataSubmittedV2 {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub ipfs_cid: String,
+    pub ipfs_gateway: String,
+    pub gateway_url: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IpfsGatewayUpdated {
+    pub admin: Pubkey,
+    pub ipfs_gateway: String,
+}
+
+#[event]
+pub struct ResponseValidated {
+    pub study_id: u64,
+    pub submission: Pubkey,
+    pub response_id: u32,
+    pub quality_score: u8,
+    pub validator: Pubkey,
+    pub timestamp: i64,
+}
+
 //  track vault creation and token distribution
 #[event]
 pub struct RewardVaultCreated {
@@ -67,15 +215,135 @@ pub struct RewardVaultCreated {
     pub initial_deposit: u64,
 }
 
+#[event]
+pub struct RewardVaultToppedUp {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+// Uniform audit stream - emitted on every consent mutation alongside the specific event
+#[event]
+pub struct ConsentStateChanged {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub old_state: u8,
+    pub new_state: u8,
+    pub timestamp: i64,
+    pub notification_config_hash: Option<[u8; 32]>,
+}
+
+#[event]
+pub struct RewardRecipientUpdated {
+    pub study_id: u64,
+    pub participant: Pubkey,
+    pub reward_recipient: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct StrayTokensRecovered {
+    pub study_id: u64,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct RewardDistributed {
     pub study_id: u64,
     pub participant: Pubkey,
     pub amount: u64,
+    // Vault balance left after this payout, so indexers can chart depletion from events alone
+    pub vault_remaining_balance: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExpiredStudyFinalized {
+    pub study_id: u64,
+    pub keeper: Pubkey,
+    pub keeper_fee: u64,
+    pub returned_to_researcher: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardsWithdrawn {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ExcessFundingWithdrawn {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub amount: u64,
+    pub remaining_obligation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RevokedSlotReclaimed {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub amount: u64,
+    pub remaining_reclaimable_slots: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardMerkleRootSet {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub merkle_root: [u8; 32],
+}
+
+#[event]
+pub struct CompletionBonusReleased {
+    pub study_id: u64,
+    pub bonus_pool: u64,
+    pub bonus_per_participant: u64,
+    pub completed_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributionRateLimitUpdated {
+    pub max_distributions_per_window: u32,
+    pub distribution_window_seconds: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProtocolPauseUpdated {
+    pub admin: Pubkey,
+    pub is_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RewardPauseUpdated {
+    pub study_id: u64,
+    pub researcher: Pubkey,
+    pub is_paused: bool,
     pub timestamp: i64,
 }
 
 //  track data collection setup
+#[event]
+pub struct FinalStatsRecorded {
+    pub study_id: u64,
+    pub total_responses: u32,
+    pub total_file_size_bytes: u64,
+    pub timestamp: i64,
+}
+
 #[event]
 pub struct SurveySchemaCreated {
     pub study_id: u64,
@@ -10,11 +10,23 @@ pub use events::*;
 pub use accounts::{
     AdminAccount,
     StudyAccount,
+    StudyMetadata,
     ConsentAccount,
+    IdentityNullifier,
+    GlobalParticipant,
+    ResearcherProfile,
+    DataHashRecord,
+    CriteriaVersion,
     SubmissionAccount,
+    ResponseRecord,
     RewardVault,
+    DistributionLedger,
+    LedgerEntry,
+    MerkleClaim,
+    StudyRating,
     SurveySchema,
     DataCollectionStats,
+    StudyFinalStats,
 };
 
 pub use constants::{
@@ -31,4 +43,4 @@ pub use constants::{
     COMPLETION_NFT_SYMBOL,
 };
 
-pub use errors::RecruSearchError;
\ No newline at end of file
+pub use errors::{RecruSearchError, study_error};
\ No newline at end of file
@@ -38,9 +38,11 @@ export function getConsentPDA(programId: PublicKey, study: PublicKey, participan
     return consentPDA;
 }
 
-export function getSubmissionPDA(study: PublicKey, participant: PublicKey): PublicKey {
+export function getSubmissionPDA(study: PublicKey, participant: PublicKey, index: number = 0): PublicKey {
+    const indexBuffer = Buffer.alloc(4);
+    indexBuffer.writeUInt32LE(index);
     const [submissionPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("submission"), study.toBuffer(), participant.toBuffer()],
+        [Buffer.from("submission"), study.toBuffer(), participant.toBuffer(), indexBuffer],
         programId
     );
     return submissionPDA;
@@ -64,6 +66,24 @@ export function getSurveySchemaPDA(study: PublicKey): PublicKey {
     return schemaPDA;
 }
 
+export function getCriteriaVersionPDA(study: PublicKey, version: number): PublicKey {
+    const versionBytes = Buffer.alloc(4);
+    versionBytes.writeUInt32LE(version);
+    const [versionPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("criteria_version"), study.toBuffer(), versionBytes],
+        programId
+    );
+    return versionPDA;
+}
+
+export function getNullifierPDA(study: PublicKey, nullifier: Buffer): PublicKey {
+    const [nullifierPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("nullifier"), study.toBuffer(), nullifier],
+        programId
+    );
+    return nullifierPDA;
+}
+
 // Vault token account
 export function getVaultTokenAccountPDA(rewardVault: PublicKey): PublicKey {
     const [vaultTokenAccount] = PublicKey.findProgramAddressSync(